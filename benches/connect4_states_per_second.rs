@@ -5,7 +5,9 @@ use criterion::measurement::{Measurement, ValueFormatter};
 use criterion::{criterion_group, criterion_main, AxisScale, BenchmarkId, Criterion, PlotConfiguration, Throughput};
 use criterion::BatchSize::SmallInput;
 use software_testing_project::connect_four;
+use software_testing_project::connect_four::proof_number::DEFAULT_MAX_NODES;
 use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::solver::{SolverConfig, SolverRegistry};
 use software_testing_project::connect_four::state::State;
 use software_testing_project::connect_four::state_bitboard::StateBitboard;
 use software_testing_project::connect_four::state_file::read_state_file;
@@ -214,34 +216,25 @@ fn different_methods_sps(c: &mut Criterion<SecondsPerStateMeasurement>) {
     let mut group = c.benchmark_group("different_methods_sps");
     group.sample_size(10);
 
-    group.bench_function("naive", |bencher| {
-        bencher.iter_batched(
-            || state.clone(),
-            |cloned_state| {
-                let ret = connect_four::naive::evaluate_position(cloned_state);
-                add_states_evaluated(ret.states_evaluated);
-            },
-            SmallInput
-        )
-    });
-
-    group.bench_function("caching", |bencher| {
-        bencher.iter_batched(
-            || state.clone(),
-            |cloned_state| {
-                let ret = connect_four::cache_strategy::evaluate_position(cloned_state);
-                add_states_evaluated(ret.states_evaluated);
-            },
-            SmallInput
-        )
-    });
+    for solver in SolverRegistry::with_default_solvers().create_all(SolverConfig::default()) {
+        group.bench_function(solver.name(), |bencher| {
+            bencher.iter_batched(
+                || state.clone(),
+                |cloned_state| {
+                    let ret = solver.evaluate(cloned_state);
+                    add_states_evaluated(ret.states_evaluated);
+                },
+                SmallInput
+            )
+        });
+    }
 
-    group.bench_function("threads", |bencher| {
+    group.bench_function("proof_number", |bencher| {
         bencher.iter_batched(
             || state.clone(),
             |cloned_state| {
-                let ret = connect_four::threads::evaluate_position(cloned_state);
-                add_states_evaluated(ret.states_evaluated);
+                let ret = connect_four::proof_number::solve(cloned_state, DEFAULT_MAX_NODES);
+                add_states_evaluated(ret.nodes_expanded);
             },
             SmallInput
         )
@@ -265,47 +258,25 @@ fn multiple_depths_sps(c: &mut Criterion<SecondsPerStateMeasurement>) {
             .summary_scale(AxisScale::Logarithmic)
     );
 
+    let solvers = SolverRegistry::<StateType>::with_default_solvers().create_all(SolverConfig::default());
+
     for depth in MIN_DEPTH..=MAX_DEPTH {
         let states: Vec<StateType> = read_state_file(depth).unwrap();
 
-        group.bench_function(BenchmarkId::new("naive", depth), |bencher| {
-            bencher.iter_batched(
-                || states.clone(),
-                |curr_states| {
-                    for state in curr_states {
-                        let ret = connect_four::naive::evaluate_position(state);
-                        add_states_evaluated(ret.states_evaluated);
-                    }
-                },
-                SmallInput
-            )
-        });
-
-        group.bench_function(BenchmarkId::new("caching", depth), |bencher| {
-            bencher.iter_batched(
-                || states.clone(),
-                |curr_states| {
-                    for state in curr_states {
-                        let ret = connect_four::cache_strategy::evaluate_position(state);
-                        add_states_evaluated(ret.states_evaluated);
-                    }
-                },
-                SmallInput
-            )
-        });
-
-        group.bench_function(BenchmarkId::new("threads", depth), |bencher| {
-            bencher.iter_batched(
-                || states.clone(),
-                |curr_states| {
-                    for state in curr_states {
-                        let ret = connect_four::threads::evaluate_position(state);
-                        add_states_evaluated(ret.states_evaluated);
-                    }
-                },
-                SmallInput
-            )
-        });
+        for solver in &solvers {
+            group.bench_function(BenchmarkId::new(solver.name(), depth), |bencher| {
+                bencher.iter_batched(
+                    || states.clone(),
+                    |curr_states| {
+                        for state in curr_states {
+                            let ret = solver.evaluate(state);
+                            add_states_evaluated(ret.states_evaluated);
+                        }
+                    },
+                    SmallInput
+                )
+            });
+        }
     }
 
     group.finish();
@@ -1,7 +1,9 @@
 use criterion::{criterion_group, criterion_main, AxisScale, BenchmarkId, Criterion, PlotConfiguration};
 use criterion::BatchSize::SmallInput;
 use software_testing_project::connect_four;
+use software_testing_project::connect_four::proof_number::DEFAULT_MAX_NODES;
 use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::solver::{SolverConfig, SolverRegistry};
 use software_testing_project::connect_four::state::State;
 use software_testing_project::connect_four::state_bitboard::StateBitboard;
 use software_testing_project::connect_four::state_file::read_state_file;
@@ -83,41 +85,19 @@ fn single_depth_time(c: &mut Criterion) {
     let mut group = c.benchmark_group("single_depth_time");
     group.sample_size(10);
 
-    group.bench_function("naive", |bencher| {
-        bencher.iter_batched(
-            || states.clone(),
-            |curr_states| {
-                for state in curr_states {
-                    connect_four::naive::evaluate_position(state);
-                }
-            },
-            SmallInput
-        )
-    });
-
-    group.bench_function("caching", |bencher| {
-        bencher.iter_batched(
-            || states.clone(),
-            |curr_states| {
-                for state in curr_states {
-                    connect_four::cache_strategy::evaluate_position(state);
-                }
-            },
-            SmallInput
-        )
-    });
-
-    group.bench_function("threads", |bencher| {
-        bencher.iter_batched(
-            || states.clone(),
-            |curr_states| {
-                for state in curr_states {
-                    connect_four::threads::evaluate_position(state);
-                }
-            },
-            SmallInput
-        )
-    });
+    for solver in SolverRegistry::with_default_solvers().create_all(SolverConfig::default()) {
+        group.bench_function(solver.name(), |bencher| {
+            bencher.iter_batched(
+                || states.clone(),
+                |curr_states| {
+                    for state in curr_states {
+                        solver.evaluate(state);
+                    }
+                },
+                SmallInput
+            )
+        });
+    }
 
     group.finish();
 }
@@ -136,20 +116,40 @@ fn multiple_depths_time(c: &mut Criterion) {
         .summary_scale(AxisScale::Logarithmic)
     );
 
+    let solvers = SolverRegistry::<StateType>::with_default_solvers().create_all(SolverConfig::default());
+
     for depth in MIN_DEPTH..=MAX_DEPTH {
         let states: Vec<StateType> = read_state_file(depth).unwrap();
 
-        group.bench_function(BenchmarkId::new("naive", depth), |bencher| {
-            bencher.iter_batched(
-                || states.clone(),
-                |curr_states| {
-                    for state in curr_states {
-                        connect_four::naive::evaluate_position(state);
-                    }
-                },
-                SmallInput
-            )
-        });
+        for solver in &solvers {
+            group.bench_function(BenchmarkId::new(solver.name(), depth), |bencher| {
+                bencher.iter_batched(
+                    || states.clone(),
+                    |curr_states| {
+                        for state in curr_states {
+                            solver.evaluate(state);
+                        }
+                    },
+                    SmallInput
+                )
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn proof_number_time(c: &mut Criterion) {
+    const MIN_DEPTH: usize = 16;
+    const MAX_DEPTH: usize = 21;
+
+    type StateType = StateBitboard;
+    let mut group = c.benchmark_group("proof_number_time");
+
+    group.sample_size(10);
+
+    for depth in MIN_DEPTH..=MAX_DEPTH {
+        let states: Vec<StateType> = read_state_file(depth).unwrap();
 
         group.bench_function(BenchmarkId::new("caching", depth), |bencher| {
             bencher.iter_batched(
@@ -163,12 +163,12 @@ fn multiple_depths_time(c: &mut Criterion) {
             )
         });
 
-        group.bench_function(BenchmarkId::new("threads", depth), |bencher| {
+        group.bench_function(BenchmarkId::new("proof_number", depth), |bencher| {
             bencher.iter_batched(
                 || states.clone(),
                 |curr_states| {
                     for state in curr_states {
-                        connect_four::threads::evaluate_position(state);
+                        connect_four::proof_number::solve(state, DEFAULT_MAX_NODES);
                     }
                 },
                 SmallInput
@@ -179,5 +179,5 @@ fn multiple_depths_time(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, single_state_time, array_vs_bitboard_time, single_depth_time, multiple_depths_time);
+criterion_group!(benches, single_state_time, array_vs_bitboard_time, single_depth_time, multiple_depths_time, proof_number_time);
 criterion_main!(benches);
@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use software_testing_project::connect_four::perft::{perft, PUBLISHED_PERFT};
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const MIN_DEPTH: usize = 4;
+const MAX_DEPTH: usize = 7;
+
+
+fn array_vs_bitboard_perft(c: &mut Criterion) {
+    let array_state = StateArray::start_state();
+    let bitboard_state = StateBitboard::start_state();
+
+    let mut group = c.benchmark_group("array_vs_bitboard_perft");
+    group.sample_size(10);
+
+    for (depth, &leaf_count) in PUBLISHED_PERFT.iter().enumerate().take(MAX_DEPTH + 1).skip(MIN_DEPTH) {
+        group.throughput(Throughput::Elements(leaf_count as u64));
+
+        group.bench_function(BenchmarkId::new("array", depth), |bencher| {
+            bencher.iter(|| perft(&array_state, depth))
+        });
+
+        group.bench_function(BenchmarkId::new("bitboard", depth), |bencher| {
+            bencher.iter(|| perft(&bitboard_state, depth))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, array_vs_bitboard_perft);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+#ifndef CONNECT_FOUR_H
+#define CONNECT_FOUR_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* return codes of every function that takes a session */
+#define C4_OK 0
+#define C4_ERROR_NULL_POINTER 1
+#define C4_ERROR_INVALID_POSITION 2
+#define C4_ERROR_GAME_OVER 3
+
+#define C4_COLS 7
+
+/* the score of a column that cannot be played */
+#define C4_FULL_COLUMN INT32_MIN
+
+/* a solver session with a cache kept between calls; evaluations may run on several threads at once,
+ * but c4_session_set_position and c4_session_free must not overlap any other call on the session */
+typedef struct C4Session C4Session;
+
+/* zero means no limit; the time limit covers the whole call */
+typedef struct C4Budget {
+    uint64_t time_ms;
+    uint64_t max_nodes;
+} C4Budget;
+
+typedef struct C4Evaluation {
+    /* from the perspective of the player to move */
+    int32_t score;
+    /* false when the budget ran out, in which case the score is only a bound */
+    bool exact;
+    uint64_t nodes;
+} C4Evaluation;
+
+typedef struct C4ColumnScores {
+    /* indexed from the leftmost column, C4_FULL_COLUMN for full columns */
+    int32_t scores[C4_COLS];
+    /* index into scores, or -1 when no column can be played */
+    int best_move;
+    bool exact;
+    uint64_t nodes;
+} C4ColumnScores;
+
+/* starts at the empty board; free with c4_session_free */
+C4Session *c4_session_new(void);
+void c4_session_free(C4Session *session);
+
+/* a move string of 1-based columns such as "4453", or a grid of rows from the top down
+ * separated by '/' with '.' for empty cells; the position is unchanged when it is invalid */
+int c4_session_set_position(C4Session *session, const char *position);
+
+/* C4_ERROR_GAME_OVER when the position is already won or drawn */
+int c4_session_evaluate(const C4Session *session, C4Budget budget, C4Evaluation *out);
+int c4_session_column_scores(const C4Session *session, C4Budget budget, C4ColumnScores *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,166 @@
+use std::env;
+use std::io;
+use std::io::{BufRead, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::game::{Game, GameStatus};
+use software_testing_project::connect_four::player::{DepthLimitedPlayer, Player, RandomPlayer, SolverPlayer};
+use software_testing_project::connect_four::solver::{CachingSession, Solver, SolverConfig};
+use software_testing_project::connect_four::solver_util::COLS;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const USAGE: &str = "usage: play [--difficulty exact|medium|easy] [--second] [--seed N]";
+const SOLVER_TIME_LIMIT: Duration = Duration::from_millis(500);
+const FALLBACK_MAX_DEPTH: usize = 42;
+const FALLBACK_TIME_LIMIT: Duration = Duration::from_secs(1);
+const MEDIUM_DEPTH: usize = 6;
+
+struct Options {
+    difficulty: String,
+    human_first: bool,
+    seed: u64,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        difficulty: "exact".to_string(),
+        human_first: true,
+        seed: 0,
+    };
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--difficulty" => options.difficulty = args.next().ok_or("--difficulty needs a value")?,
+            "--second" => options.human_first = false,
+            "--seed" => {
+                options.seed = args
+                    .next()
+                    .and_then(|seed| seed.parse().ok())
+                    .ok_or("--seed needs a number")?;
+            },
+            _ => return Err(format!("unknown argument \"{arg}\"")),
+        }
+    }
+
+    Ok(options)
+}
+
+fn solver() -> CachingSession<StateBitboard> {
+    CachingSession::new(SolverConfig::default().with_budget(SearchBudget::unlimited().with_time_limit(SOLVER_TIME_LIMIT)))
+}
+
+fn engine(difficulty: &str, seed: u64) -> Option<Box<dyn Player<StateBitboard>>> {
+    match difficulty {
+        "exact" => {
+            let fallback = DepthLimitedPlayer::new(FALLBACK_MAX_DEPTH).with_time_limit(FALLBACK_TIME_LIMIT);
+            Some(Box::new(SolverPlayer::new(Box::new(solver())).with_fallback(Box::new(fallback))))
+        },
+        "medium" => Some(Box::new(DepthLimitedPlayer::new(MEDIUM_DEPTH))),
+        "easy" => Some(Box::new(RandomPlayer::new(seed))),
+        _ => None,
+    }
+}
+
+fn hint(game: &Game<StateBitboard>, solver: &dyn Solver<StateBitboard>) -> String {
+    let scores = solver.column_scores(game.state());
+    let mut hints = vec![];
+
+    for col in 0..COLS {
+        let score = match scores.scores[col] {
+            None => "-".to_string(),
+            Some(score) if scores.exact[col] => format!("{score:+}"),
+            Some(_) => "?".to_string(),
+        };
+
+        hints.push(format!("{}:{score}", col + 1));
+    }
+
+    hints.join("  ")
+}
+
+fn human_moves(game: &Game<StateBitboard>, human_first: bool) -> usize {
+    let moves = game.moves().len();
+    if human_first { moves.div_ceil(2) } else { moves / 2 }
+}
+
+fn is_human_turn(game: &Game<StateBitboard>, human_first: bool) -> bool {
+    ((game.moves().len() & 1) == 0) == human_first
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return ExitCode::from(2);
+        },
+    };
+
+    let Some(mut engine) = engine(&options.difficulty, options.seed) else {
+        eprintln!("unknown difficulty \"{}\"\n{USAGE}", options.difficulty);
+        return ExitCode::from(2);
+    };
+
+    let hint_solver = solver();
+    let mut game: Game<StateBitboard> = Game::new();
+    let mut lines = io::stdin().lock().lines();
+
+    println!("playing against the {} engine; you are {}", engine.name(), if options.human_first { 'X' } else { 'O' });
+
+    loop {
+        print!("\n{game}");
+
+        match game.status() {
+            GameStatus::Won(winner) => println!("{winner} wins"),
+            GameStatus::Draw => println!("draw"),
+            GameStatus::InProgress if !is_human_turn(&game, options.human_first) => {
+                let col = engine.choose_move(game.state()).unwrap();
+                println!("engine plays {}", col + 1);
+                game.play(col).unwrap();
+                continue;
+            },
+            GameStatus::InProgress => {},
+        }
+
+        print!("{} to move: 1-{COLS}, u(ndo), h(int), q(uit) > ", game.player_to_move());
+        io::stdout().flush().unwrap();
+
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            return ExitCode::SUCCESS;
+        };
+
+        match line.trim() {
+            "q" | "quit" => return ExitCode::SUCCESS,
+            "u" | "undo" => {
+                if human_moves(&game, options.human_first) == 0 {
+                    println!("nothing to undo");
+                    continue;
+                }
+
+                game.undo();
+
+                if !is_human_turn(&game, options.human_first) {
+                    game.undo();
+                }
+            },
+            "h" | "hint" => {
+                if game.status() == GameStatus::InProgress {
+                    println!("{}", hint(&game, &hint_solver));
+                }
+            },
+            input => match input.parse::<usize>() {
+                Ok(col) if game.status() == GameStatus::InProgress => {
+                    if let Err(err) = game.play(col.wrapping_sub(1)) {
+                        println!("{err}");
+                    }
+                },
+                Ok(_) => println!("the game is over; undo or quit"),
+                Err(_) => println!("unknown command \"{input}\""),
+            },
+        }
+    }
+}
@@ -7,3 +7,29 @@ pub mod solver_util;
 pub mod state_file;
 pub mod state_bitboard;
 pub mod state;
+pub mod budget;
+pub mod search_stats;
+pub mod solver;
+pub mod batch;
+pub mod heuristic;
+pub mod depth_limited;
+pub mod mcts;
+pub mod proof_number;
+pub mod tablebase;
+pub mod perft;
+pub mod move_string;
+pub mod equivalence;
+pub mod game_record;
+pub mod analysis;
+pub mod puzzle;
+pub mod player;
+pub mod game;
+pub mod engine;
+pub mod service;
+pub mod server;
+pub mod ffi;
+pub mod tournament;
+pub mod progress;
+pub mod trace;
+pub mod checkpoint;
+pub mod distributed;
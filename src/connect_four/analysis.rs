@@ -0,0 +1,171 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Serializer};
+use crate::connect_four::game_record::{GameRecord, GameRecordError};
+use crate::connect_four::solver::Solver;
+use crate::connect_four::solver_util::{Outcome, COLS};
+use crate::connect_four::state::State;
+
+// scores are from the perspective of the player making the move
+#[derive(Clone, Debug, Serialize)]
+pub struct PlyAnalysis {
+    pub ply: usize,
+    #[serde(serialize_with = "serialize_column")]
+    pub col: usize,
+    pub score_before: i32,
+    pub score_after: i32,
+    #[serde(serialize_with = "serialize_columns")]
+    pub optimal_columns: Vec<usize>,
+    pub column_scores: [Option<i32>; COLS],
+    pub exact: bool,
+    pub blunder: bool,
+}
+
+impl PlyAnalysis {
+    pub fn score_loss(&self) -> i32 {
+        self.score_before - self.score_after
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameAnalysis {
+    pub first_player: String,
+    pub second_player: String,
+    pub result: String,
+    pub plies: Vec<PlyAnalysis>,
+    pub states_evaluated: usize,
+    #[serde(rename = "wall_time_secs", serialize_with = "serialize_secs")]
+    pub wall_time: Duration,
+}
+
+impl GameAnalysis {
+    pub fn blunders(&self) -> impl Iterator<Item = &PlyAnalysis> {
+        self.plies.iter().filter(|ply| ply.blunder)
+    }
+
+    // writes the score of every analysed move into the record and marks the blunders
+    pub fn annotate(&self, record: &mut GameRecord) {
+        for ply in &self.plies {
+            let recorded_move = &mut record.moves[ply.ply - 1];
+            recorded_move.eval = Some(ply.score_after);
+
+            if ply.blunder {
+                recorded_move.comment = Some(format!("blunder, best was {}", format_columns(&ply.optimal_columns)));
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("game analysis is always serializable")
+    }
+}
+
+fn format_columns(cols: &[usize]) -> String {
+    cols.iter().map(|col| (col + 1).to_string()).collect::<Vec<_>>().join(",")
+}
+
+fn serialize_column<Ser: Serializer>(col: &usize, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.serialize_u64(*col as u64 + 1)
+}
+
+fn serialize_columns<Ser: Serializer>(cols: &[usize], serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.collect_seq(cols.iter().map(|col| col + 1))
+}
+
+fn serialize_secs<Ser: Serializer>(duration: &Duration, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl fmt::Display for GameAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} vs {} ({})", self.first_player, self.second_player, self.result)?;
+        writeln!(f, "ply  col  before  after  loss  optimal")?;
+
+        for ply in &self.plies {
+            write!(
+                f,
+                "{:>3}  {:>3}  {:>+6}  {:>+5}  {:>4}  {}",
+                ply.ply,
+                ply.col + 1,
+                ply.score_before,
+                ply.score_after,
+                ply.score_loss(),
+                format_columns(&ply.optimal_columns),
+            )?;
+
+            if ply.blunder {
+                write!(f, "  blunder")?;
+            }
+
+            if !ply.exact {
+                write!(f, "  (inexact)")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        writeln!(f, "blunders:          {}", self.blunders().count())?;
+        writeln!(f, "states evaluated:  {}", self.states_evaluated)?;
+        writeln!(f, "wall time:         {:.3?}", self.wall_time)
+    }
+}
+
+// analyses every move from `first_ply` (0-based) onwards; early plies can be skipped since
+// solving positions near the start of the game is expensive.
+// pass a solver that keeps its cache between calls (`CachingSession` or `ThreadsSession`)
+// so the plies share their work
+pub fn analyze_game<S: State>(
+    record: &GameRecord,
+    solver: &dyn Solver<S>,
+    first_ply: usize,
+) -> Result<GameAnalysis, GameRecordError> {
+
+    let start = Instant::now();
+    let states = record.replay::<S>()?;
+    let mut plies = vec![];
+    let mut states_evaluated = 0;
+
+    // going backwards lets every ply reuse the bounds cached while solving the later ones
+    for (index, recorded_move) in record.moves.iter().enumerate().skip(first_ply).rev() {
+        let scores = solver.column_scores(&states[index]);
+        states_evaluated += scores.states_evaluated;
+
+        let column_scores = scores.scores;
+        let score_before = scores.best_score().unwrap();
+        let score_after = column_scores[recorded_move.col].unwrap();
+
+        // a blunder gives away a win, so both the win and the move's score have to be proven
+        let is_win = |col: usize| column_scores[col].is_some_and(|score| Outcome::from_eval(score) == Outcome::Win);
+        let blunder = (0..COLS).any(|col| scores.exact[col] && is_win(col))
+            && scores.exact[recorded_move.col]
+            && !is_win(recorded_move.col);
+
+        let mut optimal_columns: Vec<usize> = (0..COLS).filter(|&col| column_scores[col] == Some(score_before)).collect();
+
+        // list the column the solver would pick first
+        let best = scores.best_column().unwrap();
+        optimal_columns.sort_unstable_by_key(|&col| col != best);
+
+        plies.push(PlyAnalysis {
+            ply: index + 1,
+            col: recorded_move.col,
+            score_before,
+            score_after,
+            optimal_columns,
+            column_scores,
+            exact: scores.is_exact(),
+            blunder,
+        });
+    }
+
+    plies.reverse();
+
+    Ok(GameAnalysis {
+        first_player: record.first_player.clone(),
+        second_player: record.second_player.clone(),
+        result: record.result.as_str().to_string(),
+        plies,
+        states_evaluated,
+        wall_time: start.elapsed(),
+    })
+}
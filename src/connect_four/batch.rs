@@ -0,0 +1,117 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::search_stats::SearchStats;
+use crate::connect_four::solver::Solver;
+use crate::connect_four::solver_util::EvaluatePositionReturn;
+use crate::connect_four::state::State;
+
+pub struct BatchResult {
+    pub results: Vec<EvaluatePositionReturn>,
+    pub stats: BatchStats,
+}
+
+#[derive(Default)]
+pub struct BatchStats {
+    pub positions: usize,
+    pub exact_results: usize,
+    pub states_evaluated: usize,
+    pub wall_time: Duration,
+    pub search: Option<SearchStats>,
+}
+
+impl BatchStats {
+    fn add(&mut self, ret: &EvaluatePositionReturn) {
+        self.positions += 1;
+        self.states_evaluated += ret.states_evaluated;
+
+        if ret.is_exact() {
+            self.exact_results += 1;
+        }
+
+        if let Some(stats) = &ret.stats {
+            self.search.get_or_insert_with(|| SearchStats::new(stats.root_ply)).merge(stats);
+        }
+    }
+
+    pub fn states_per_second(&self) -> f64 {
+        self.states_evaluated as f64 / self.wall_time.as_secs_f64()
+    }
+}
+
+impl fmt::Display for BatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "positions:         {}", self.positions)?;
+        writeln!(f, "exact results:     {}", self.exact_results)?;
+        writeln!(f, "states evaluated:  {}", self.states_evaluated)?;
+        writeln!(f, "wall time:         {:.3?}", self.wall_time)?;
+        writeln!(f, "states per second: {:.0}", self.states_per_second())
+    }
+}
+
+pub fn default_thread_count() -> usize {
+    thread::available_parallelism().map_or(1, |count| count.get())
+}
+
+// maps every item on a pool of `thread_count` workers, keeping the input order
+pub fn parallel_map<T, R, F>(items: Vec<T>, thread_count: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let item_count = items.len();
+    let queue: Vec<Mutex<Option<T>>> = items.into_iter().map(|item| Mutex::new(Some(item))).collect();
+    let next_index = AtomicUsize::new(0);
+
+    let mut indexed_results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..thread_count.clamp(1, item_count.max(1)))
+            .map(|_| scope.spawn(|| {
+                let mut worker_results = vec![];
+
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+
+                    if index >= item_count {
+                        break;
+                    }
+
+                    let item = queue[index].lock().unwrap().take().unwrap();
+                    worker_results.push((index, f(item)));
+                }
+
+                worker_results
+            }))
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+
+    indexed_results.sort_unstable_by_key(|(index, _)| *index);
+    indexed_results.into_iter().map(|(_, result)| result).collect()
+}
+
+// `budget` applies to each position on its own. solvers that are not `parallel_safe` get the
+// positions one at a time
+pub fn solve_batch<S: State>(states: Vec<S>, solver: &dyn Solver<S>, budget: &SearchBudget, thread_count: usize) -> BatchResult {
+    let start = Instant::now();
+    let config = solver.config().clone().with_budget(budget.clone());
+    let thread_count = if solver.parallel_safe() { thread_count } else { 1 };
+    let results = parallel_map(states, thread_count, |state| solver.evaluate_with_config(state, &config));
+
+    let mut stats = BatchStats::default();
+
+    for ret in &results {
+        stats.add(ret);
+    }
+
+    stats.wall_time = start.elapsed();
+
+    BatchResult {
+        results,
+        stats,
+    }
+}
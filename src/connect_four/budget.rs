@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// reading the clock every node is noticeably slower than the search itself
+const DEADLINE_CHECK_INTERVAL: usize = 1 << 10;
+
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SearchBudget {
+    pub max_nodes: Option<usize>,
+    pub time_limit: Option<Duration>,
+    pub cancel_token: Option<CancelToken>,
+}
+
+impl SearchBudget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn with_cancel_token(mut self, cancel_token: CancelToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    // for several searches in turn that share this budget, so that a time limit covers all of them
+    pub fn start_shared(&self) -> SharedBudget {
+        SharedBudget {
+            max_nodes: self.max_nodes,
+            deadline: self.time_limit.map(|time_limit| Instant::now() + time_limit),
+            cancel_token: self.cancel_token.clone(),
+            nodes: 0,
+        }
+    }
+
+    pub fn start(&self) -> BudgetTracker {
+        BudgetTracker {
+            max_nodes: self.max_nodes.unwrap_or(usize::MAX),
+            deadline: self.time_limit.map(|time_limit| Instant::now() + time_limit),
+            cancel_token: self.cancel_token.clone(),
+            exhausted: false,
+        }
+    }
+}
+
+pub struct SharedBudget {
+    max_nodes: Option<usize>,
+    deadline: Option<Instant>,
+    cancel_token: Option<CancelToken>,
+    nodes: usize,
+}
+
+impl SharedBudget {
+    // what the searches so far have left for the next one
+    pub fn remaining(&self) -> SearchBudget {
+        SearchBudget {
+            max_nodes: self.max_nodes.map(|max_nodes| max_nodes.saturating_sub(self.nodes)),
+            time_limit: self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now())),
+            cancel_token: self.cancel_token.clone(),
+        }
+    }
+
+    pub fn spend(&mut self, nodes: usize) {
+        self.nodes += nodes;
+    }
+
+    pub fn spent(&self) -> usize {
+        self.nodes
+    }
+}
+
+pub struct BudgetTracker {
+    max_nodes: usize,
+    deadline: Option<Instant>,
+    cancel_token: Option<CancelToken>,
+    exhausted: bool,
+}
+
+impl BudgetTracker {
+    pub fn is_exhausted(&mut self, nodes: usize) -> bool {
+        if self.exhausted {
+            return true
+        }
+
+        self.exhausted = nodes >= self.max_nodes
+            || self.cancel_token.as_ref().is_some_and(CancelToken::is_cancelled)
+            || (nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+                && self.deadline.is_some_and(|deadline| Instant::now() >= deadline));
+
+        self.exhausted
+    }
+}
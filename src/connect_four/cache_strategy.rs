@@ -1,39 +1,142 @@
 use std::cmp::{max, min};
 use std::collections::HashMap;
-use crate::connect_four::solver_util::{EvaluatePositionReturn, DRAW, WORST_EVAL, BEST_EVAL};
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::solver_util::{nth_move, search_root, EvaluatePositionReturn, SearchContext, COLS, DRAW, ROWS, WORST_EVAL, BEST_EVAL};
 use crate::connect_four::naive;
+use crate::connect_four::solver::SolverConfig;
 use crate::connect_four::state::State;
 
 // adjusted for performance tuning
 const MAX_CACHED_DEPTH: usize = 35;
 
-struct GlobalState<S: State> {
-    cache: StateCache<S>,
-    positions_evaluated: usize
+const CACHE_FILE_MAGIC: &[u8; 4] = b"C4TT";
+const CACHE_FILE_VERSION: u8 = 1;
+
+struct GlobalState<'a, S: State> {
+    cache: &'a mut StateCache<S>,
+    search: SearchContext,
 }
 
-impl<S: State> GlobalState<S> {
-    fn new() -> Self {
+impl<'a, S: State> GlobalState<'a, S> {
+    fn new(cache: &'a mut StateCache<S>, search: SearchContext) -> Self {
         Self {
-            cache: StateCache::new(),
-            positions_evaluated: 0
+            cache,
+            search,
         }
     }
+
+    fn fetch_alpha_bound(&mut self, state: &S) -> i32 {
+        let bound = self.cache.get_alpha_bound(state);
+        self.search.record_cache_probe(bound.is_some());
+        bound.unwrap_or(WORST_EVAL)
+    }
+
+    fn fetch_beta_bound(&mut self, state: &S) -> i32 {
+        let bound = self.cache.get_beta_bound(state);
+        self.search.record_cache_probe(bound.is_some());
+        bound.unwrap_or(BEST_EVAL)
+    }
 }
 
-struct StateCache<S: State> {
+// bounds stay valid across searches, so a cache can be kept between calls
+pub struct StateCache<S: State> {
     alpha_cache: HashMap<S, i32>,
     beta_cache: HashMap<S, i32>,
 }
 
 impl<S: State> StateCache<S> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             alpha_cache: HashMap::new(),
             beta_cache: HashMap::new(),
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.alpha_cache.len() + self.beta_cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alpha_cache.is_empty() && self.beta_cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.alpha_cache.clear();
+        self.beta_cache.clear();
+    }
+
+    // writes to a temporary file first, so an interrupted save leaves the previous file intact.
+    // the header records the version, the board size and the state representation
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+
+        writer.write_all(&cache_file_header::<S>())?;
+
+        for cache in [&self.alpha_cache, &self.beta_cache] {
+            writer.write_all(&(cache.len() as u64).to_le_bytes())?;
+
+            for (state, bound) in cache {
+                writer.write_all(&state.key().to_le_bytes())?;
+                writer.write_all(&(*bound as i8).to_le_bytes())?;
+            }
+        }
+
+        writer.flush()?;
+        drop(writer);
+        fs::rename(temp_path, path)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let expected_header = cache_file_header::<S>();
+
+        // a file too short for a header is as wrong as one with a different header
+        let mut header = vec![];
+        (&mut reader).take(expected_header.len() as u64).read_to_end(&mut header)?;
+
+        if header != expected_header {
+            let message = format!(
+                "not a cache for a {ROWS}x{COLS} board with the {} representation",
+                S::REPRESENTATION,
+            );
+
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let mut cache = Self::new();
+
+        for bounds in [&mut cache.alpha_cache, &mut cache.beta_cache] {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len).map_err(truncated_cache_file)?;
+
+            let len = u64::from_le_bytes(len) as usize;
+            let mut entry = [0; 9];
+            // a damaged length should fail on the missing entries rather than on the allocation
+            bounds.reserve(len.min(1 << 26));
+
+            for _ in 0..len {
+                reader.read_exact(&mut entry).map_err(truncated_cache_file)?;
+
+                let key = u64::from_le_bytes(entry[..8].try_into().unwrap());
+                let state = S::from_key(key)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("invalid position key {key:#x}")))?;
+
+                bounds.insert(state, entry[8] as i8 as i32);
+            }
+        }
+
+        Ok(cache)
+    }
+
     fn insert_alpha_bound(&mut self, state: S, bound: i32) {
         self.alpha_cache.insert(state, bound);
     }
@@ -42,12 +145,33 @@ impl<S: State> StateCache<S> {
         self.beta_cache.insert(state, bound);
     }
 
-    fn fetch_alpha_bound(&mut self, state: &S) -> i32 {
-        *self.alpha_cache.get(state).unwrap_or(&WORST_EVAL)
+    fn get_alpha_bound(&self, state: &S) -> Option<i32> {
+        self.alpha_cache.get(state).copied()
     }
 
-    fn fetch_beta_bound(&mut self, state: &S) -> i32 {
-        *self.beta_cache.get(state).unwrap_or(&BEST_EVAL)
+    fn get_beta_bound(&self, state: &S) -> Option<i32> {
+        self.beta_cache.get(state).copied()
+    }
+}
+
+fn truncated_cache_file(err: io::Error) -> io::Error {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        io::Error::new(io::ErrorKind::InvalidData, "truncated cache file")
+    } else {
+        err
+    }
+}
+
+fn cache_file_header<S: State>() -> Vec<u8> {
+    let mut header = CACHE_FILE_MAGIC.to_vec();
+    header.extend([CACHE_FILE_VERSION, ROWS as u8, COLS as u8, S::REPRESENTATION.len() as u8]);
+    header.extend(S::REPRESENTATION.as_bytes());
+    header
+}
+
+impl<S: State> Default for StateCache<S> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -56,69 +180,128 @@ fn evaluate_position_rec<S: State>(
     mut alpha: i32,
     mut beta: i32,
     global_state: &mut GlobalState<S>
-) -> i32 {
+) -> Option<i32> {
 
     if state.moves_made() > MAX_CACHED_DEPTH {
-        return naive::evaluate_position_rec(state, alpha, beta, &mut global_state.positions_evaluated);
+        return naive::evaluate_position_rec(state, alpha, beta, &mut global_state.search);
+    }
+
+    if global_state.search.out_of_budget() {
+        return None
     }
 
-    global_state.positions_evaluated += 1;
+    global_state.search.count_node(state.moves_made());
+    global_state.search.report_progress(|| Some(global_state.cache.len()));
+    global_state.search.checkpoint_cache(|path| global_state.cache.save(path));
 
     if state.board_full() {
-        return DRAW;
+        return Some(DRAW);
     }
 
-    alpha = max(alpha, global_state.cache.fetch_alpha_bound(&state));
-    beta = min(beta, global_state.cache.fetch_beta_bound(&state));
+    if let Some(eval) = global_state.search.probe_tablebase(&state) {
+        return Some(eval);
+    }
+
+    alpha = max(alpha, global_state.fetch_alpha_bound(&state));
+    beta = min(beta, global_state.fetch_beta_bound(&state));
 
     let next_states = state.next_states();
 
     for next_state in &next_states {
 
         if next_state.is_win() {
-            return state.max_eval();
+            return Some(state.max_eval());
         }
 
-        alpha = max(alpha, -global_state.cache.fetch_beta_bound(next_state));
+        alpha = max(alpha, -global_state.fetch_beta_bound(next_state));
+    }
+
+    // the cached bounds alone decide the result. searching on with the closed window would
+    // cut off at the first move and cache the window's edge as if it were a proven bound
+    if alpha >= beta {
+        return Some(alpha);
     }
 
-    for next_state in next_states {
+    for (move_index, next_state) in next_states.into_iter().enumerate() {
 
-        let eval = -evaluate_position_rec(
+        global_state.search.trace_enter(|| nth_move(&state, move_index), (-beta, -alpha));
+
+        let eval = evaluate_position_rec(
             next_state,
             -beta,
             -alpha,
             global_state,
         );
 
+        global_state.search.trace_exit(eval);
+        let eval = -eval?;
+
         alpha = max(alpha, eval);
 
         if alpha >= beta {
+            global_state.search.record_cutoff(move_index);
             global_state.cache.insert_alpha_bound(state, alpha);
-            return alpha;
+            return Some(alpha);
         }
     }
 
     global_state.cache.insert_beta_bound(state, alpha);
-    alpha
+    Some(alpha)
 }
 
 pub fn evaluate_position<S: State>(state: S) -> EvaluatePositionReturn {
+    evaluate_position_with_budget(state, &SearchBudget::unlimited())
+}
+
+pub fn evaluate_position_with_budget<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()))
+}
+
+pub fn evaluate_position_with_stats<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()).with_stats(true))
+}
+
+pub fn evaluate_position_with_config<S: State>(state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+    evaluate_position_with_cache(state, &mut StateCache::new(), config)
+}
+
+pub fn evaluate_position_with_cache<S: State>(
+    state: S,
+    cache: &mut StateCache<S>,
+    config: &SolverConfig,
+) -> EvaluatePositionReturn {
 
-    let mut global_state = GlobalState::new();
+    let mut global_state = GlobalState::new(cache, SearchContext::for_root(config, &state));
+    global_state.search.count_node(state.moves_made());
 
-    let eval = evaluate_position_rec(state, WORST_EVAL, BEST_EVAL, &mut global_state);
+    let (eval, bound) = search_root(&state, &global_state.search.finished_root_moves(), |col, next_state, alpha, beta| {
+        global_state.search.start_root_move(col, (-beta, -alpha));
+        global_state.search.trace_enter(|| col, (alpha, beta));
+        let eval = evaluate_position_rec(next_state, alpha, beta, &mut global_state);
+        global_state.search.trace_exit(eval);
+        global_state.search.finish_root_move(col, eval);
+        eval
+    });
 
-    EvaluatePositionReturn::new(eval, global_state.positions_evaluated)
+    let mut ret = EvaluatePositionReturn::bounded(eval, global_state.search.states_evaluated, bound);
+    ret.stats = global_state.search.finish_stats().map(|mut stats| {
+        // the caches only grow during a search, so their final size is the peak
+        stats.record_cache_sizes(global_state.cache.alpha_cache.len(), global_state.cache.beta_cache.len());
+        stats
+    });
+    ret.trace = global_state.search.finish_trace(eval);
+    ret.checkpoint_error = global_state.search.finish_checkpoint(bound, |path| global_state.cache.save(path));
+    ret
 }
 
 pub fn optimal_next_state<S: State>(state: S) -> S {
-    let mut global_state = GlobalState::new();
+    let mut cache = StateCache::new();
+    let mut global_state = GlobalState::new(&mut cache, SearchContext::new(&SearchBudget::unlimited()));
     let mut max_eval = WORST_EVAL;
     let mut optimal_state = state.clone();
 
     for next_state in state.next_states() {
-        let eval = evaluate_position_rec(next_state.clone(), max_eval, BEST_EVAL, &mut global_state);
+        let eval = evaluate_position_rec(next_state.clone(), max_eval, BEST_EVAL, &mut global_state).unwrap();
 
         if eval > max_eval {
             max_eval = eval;
@@ -0,0 +1,241 @@
+use std::cmp::max;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::connect_four::move_string::format_grid;
+use crate::connect_four::solver_util::{BEST_EVAL, WORST_EVAL};
+use crate::connect_four::state::State;
+
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(600);
+
+// reading the clock every node is noticeably slower than the search itself
+const CHECKPOINT_CHECK_INTERVAL: usize = 1 << 12;
+
+#[derive(Clone, Debug)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    // how often the cache is saved. the root state is written whenever a root move starts or finishes
+    pub interval: Duration,
+    // only the caching search saves its cache, next to the checkpoint at `cache_path`
+    pub save_cache: bool,
+    // a checkpoint of an interrupted search, ignored when it is for another position
+    pub resume: Option<RootCheckpoint>,
+}
+
+impl CheckpointConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: DEFAULT_CHECKPOINT_INTERVAL,
+            save_cache: false,
+            resume: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_cache(mut self, save_cache: bool) -> Self {
+        self.save_cache = save_cache;
+        self
+    }
+
+    pub fn resuming(mut self, checkpoint: RootCheckpoint) -> Self {
+        self.resume = Some(checkpoint);
+        self
+    }
+
+    // in the format of `StateCache::save`, so resuming with it is a `StateCache::load` away
+    pub fn cache_path(&self) -> PathBuf {
+        let mut path = self.path.as_os_str().to_owned();
+        path.push(".cache");
+        PathBuf::from(path)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FinishedRootMove {
+    #[serde(serialize_with = "serialize_column", deserialize_with = "deserialize_column")]
+    pub col: usize,
+    // from the root's side. a score at or below the window's lower bound when the move was
+    // searched only shows that the move is no better than the moves before it
+    pub score: i32,
+}
+
+// the root-level state of a search, written as JSON
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RootCheckpoint {
+    // the position as a grid, see `move_string::format_grid`
+    pub position: String,
+    // in the order they were searched
+    pub finished: Vec<FinishedRootMove>,
+    // the root's window when the checkpoint was written
+    pub window: (i32, i32),
+    // the move being searched when the checkpoint was written
+    #[serde(serialize_with = "serialize_optional_column", deserialize_with = "deserialize_optional_column")]
+    pub current_move: Option<usize>,
+    // over every run of the search
+    pub elapsed_secs: f64,
+    pub complete: bool,
+}
+
+impl RootCheckpoint {
+    pub fn new<S: State>(state: &S) -> Self {
+        Self {
+            position: format_grid(state),
+            finished: vec![],
+            window: (WORST_EVAL, BEST_EVAL),
+            current_move: None,
+            elapsed_secs: 0.0,
+            complete: false,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        serde_json::from_str(&fs::read_to_string(path)?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    // writes to a temporary file first, so an interrupted save leaves the previous checkpoint intact
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+
+        let json = serde_json::to_string_pretty(self).expect("checkpoints are always serializable");
+        fs::write(&temp_path, json + "\n")?;
+        fs::rename(temp_path, path)
+    }
+
+    pub fn is_for<S: State>(&self, state: &S) -> bool {
+        self.position == format_grid(state)
+    }
+}
+
+fn serialize_column<Ser: Serializer>(col: &usize, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.serialize_u64(*col as u64 + 1)
+}
+
+fn deserialize_column<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    match usize::deserialize(deserializer)? {
+        0 => Err(serde::de::Error::custom("columns start at 1")),
+        col => Ok(col - 1),
+    }
+}
+
+fn serialize_optional_column<Ser: Serializer>(col: &Option<usize>, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    match col {
+        Some(col) => serializer.serialize_some(&(col + 1)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_optional_column<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    match Option::<usize>::deserialize(deserializer)? {
+        Some(0) => Err(serde::de::Error::custom("columns start at 1")),
+        col => Ok(col.map(|col| col - 1)),
+    }
+}
+
+// writes the checkpoint as the root search goes. a failed write does not stop the search;
+// the first error is kept for the result
+pub struct Checkpointer {
+    config: CheckpointConfig,
+    checkpoint: RootCheckpoint,
+    previous_elapsed: f64,
+    started: Instant,
+    next_cache_save: Instant,
+    error: Option<io::Error>,
+}
+
+impl Checkpointer {
+    pub fn new<S: State>(config: &CheckpointConfig, state: &S) -> Self {
+        let checkpoint = config
+            .resume
+            .clone()
+            .filter(|checkpoint| checkpoint.is_for(state))
+            .unwrap_or_else(|| RootCheckpoint::new(state));
+
+        let started = Instant::now();
+
+        let mut checkpointer = Self {
+            config: config.clone(),
+            previous_elapsed: checkpoint.elapsed_secs,
+            checkpoint,
+            started,
+            next_cache_save: started + config.interval,
+            error: None,
+        };
+
+        checkpointer.checkpoint.complete = false;
+        checkpointer.write();
+        checkpointer
+    }
+
+    // the moves a resumed search can skip, with their scores
+    pub fn finished_moves(&self) -> Vec<(usize, i32)> {
+        self.checkpoint.finished.iter().map(|finished| (finished.col, finished.score)).collect()
+    }
+
+    pub fn start_root_move(&mut self, col: usize) {
+        self.checkpoint.current_move = Some(col);
+        self.write();
+    }
+
+    pub fn finish_root_move(&mut self, col: usize, score: i32) {
+        self.checkpoint.finished.push(FinishedRootMove { col, score });
+        self.checkpoint.window.0 = max(self.checkpoint.window.0, score);
+        self.checkpoint.current_move = None;
+        self.write();
+    }
+
+    // `save_cache` is only called when the cache is due to be saved
+    pub fn maybe_save_cache(&mut self, nodes: usize, save_cache: impl FnOnce(&Path) -> io::Result<()>) {
+        if !self.config.save_cache || !nodes.is_multiple_of(CHECKPOINT_CHECK_INTERVAL) {
+            return
+        }
+
+        let now = Instant::now();
+
+        if now < self.next_cache_save {
+            return
+        }
+
+        self.record(save_cache(&self.config.cache_path()));
+        self.next_cache_save = Instant::now() + self.config.interval;
+        self.write();
+    }
+
+    // `save_cache` is only called when the cache is saved with the checkpoint
+    pub fn finish(mut self, complete: bool, save_cache: impl FnOnce(&Path) -> io::Result<()>) -> Option<io::Error> {
+        if self.config.save_cache {
+            self.record(save_cache(&self.config.cache_path()));
+        }
+
+        // an interrupted search keeps the move it was searching
+        if complete {
+            self.checkpoint.current_move = None;
+        }
+
+        self.checkpoint.complete = complete;
+        self.write();
+        self.error
+    }
+
+    fn write(&mut self) {
+        self.checkpoint.elapsed_secs = self.previous_elapsed + self.started.elapsed().as_secs_f64();
+        let result = self.checkpoint.save(&self.config.path);
+        self.record(result);
+    }
+
+    fn record(&mut self, result: io::Result<()>) {
+        if let Err(err) = result
+            && self.error.is_none()
+        {
+            self.error = Some(err);
+        }
+    }
+}
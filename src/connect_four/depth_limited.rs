@@ -0,0 +1,147 @@
+use std::cmp::max;
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::heuristic::StaticEvaluation;
+use crate::connect_four::solver_util::{SearchContext, BOARD_SIZE, COLS, DEFAULT_MOVE_ORDER, DRAW};
+
+// proven results are offset past any heuristic value so they always dominate it
+pub const PROVEN_SCORE_OFFSET: i32 = 1_000;
+
+pub struct DepthLimitedResult {
+    pub score: i32,
+    pub best_move: Option<usize>,
+    pub depth: usize,
+    pub states_evaluated: usize,
+}
+
+impl DepthLimitedResult {
+    // the exact solver's score when the search proved the result
+    pub fn proven_eval(&self) -> Option<i32> {
+        if self.score.abs() > PROVEN_SCORE_OFFSET {
+            Some(self.score.signum() * (self.score.abs() - PROVEN_SCORE_OFFSET))
+        } else {
+            None
+        }
+    }
+}
+
+fn evaluate_position_rec<S: StaticEvaluation>(
+    state: S,
+    depth: usize,
+    mut alpha: i32,
+    beta: i32,
+    ctx: &mut SearchContext,
+) -> Option<i32> {
+
+    if ctx.out_of_budget() {
+        return None
+    }
+
+    ctx.count_node(state.moves_made());
+
+    if state.board_full() {
+        return Some(DRAW);
+    }
+
+    let next_states = state.next_states();
+
+    if next_states.iter().any(S::is_win) {
+        return Some(PROVEN_SCORE_OFFSET + state.max_eval());
+    }
+
+    if depth == 0 {
+        return Some(state.static_eval().clamp(1 - PROVEN_SCORE_OFFSET, PROVEN_SCORE_OFFSET - 1));
+    }
+
+    for (move_index, next_state) in next_states.into_iter().enumerate() {
+
+        let eval = -evaluate_position_rec(next_state, depth - 1, -beta, -alpha, ctx)?;
+
+        alpha = max(alpha, eval);
+
+        if alpha >= beta {
+            ctx.record_cutoff(move_index);
+            return Some(alpha);
+        }
+    }
+
+    Some(alpha)
+}
+
+fn search_root<S: StaticEvaluation>(
+    state: &S,
+    depth: usize,
+    move_order: &[usize; COLS],
+    ctx: &mut SearchContext,
+) -> Option<(i32, Option<usize>)> {
+
+    let mut alpha = -PROVEN_SCORE_OFFSET - BOARD_SIZE as i32;
+    let mut best_move = None;
+
+    for &col in move_order {
+        let Some(next_state) = state.play_move(col) else {
+            continue
+        };
+
+        let eval = if next_state.is_win() {
+            PROVEN_SCORE_OFFSET + state.max_eval()
+        } else {
+            -evaluate_position_rec(next_state, depth - 1, -PROVEN_SCORE_OFFSET - BOARD_SIZE as i32, -alpha, ctx)?
+        };
+
+        if best_move.is_none() || eval > alpha {
+            alpha = eval;
+            best_move = Some(col);
+        }
+    }
+
+    Some((alpha, best_move))
+}
+
+pub fn evaluate_position<S: StaticEvaluation>(state: S, depth: usize) -> DepthLimitedResult {
+    let mut ctx = SearchContext::new(&SearchBudget::unlimited());
+    let (score, best_move) = search_root(&state, depth.max(1), &DEFAULT_MOVE_ORDER, &mut ctx).unwrap();
+
+    DepthLimitedResult {
+        score,
+        best_move,
+        depth: depth.max(1),
+        states_evaluated: ctx.states_evaluated,
+    }
+}
+
+// iterative deepening, keeping the deepest iteration that finished within the budget
+pub fn search_with_budget<S: StaticEvaluation>(state: &S, max_depth: usize, budget: &SearchBudget) -> DepthLimitedResult {
+    let mut ctx = SearchContext::new(budget);
+    let mut move_order = DEFAULT_MOVE_ORDER;
+
+    let mut result = DepthLimitedResult {
+        score: 0,
+        best_move: move_order.into_iter().find(|&col| state.play_move(col).is_some()),
+        depth: 0,
+        states_evaluated: 0,
+    };
+
+    let remaining_moves = BOARD_SIZE - state.moves_made();
+
+    for depth in 1..=max_depth.min(remaining_moves) {
+        let Some((score, best_move)) = search_root(state, depth, &move_order, &mut ctx) else {
+            break
+        };
+
+        result.score = score;
+        result.best_move = best_move;
+        result.depth = depth;
+
+        if let Some(best_move) = best_move {
+            let position = move_order.iter().position(|&col| col == best_move).unwrap();
+            move_order[..=position].rotate_right(1);
+        }
+
+        if result.proven_eval().is_some() {
+            break
+        }
+    }
+
+    result.states_evaluated = ctx.states_evaluated;
+    result
+}
@@ -0,0 +1,446 @@
+use std::cmp::max;
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::net::Shutdown;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+#[cfg(unix)]
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use crate::connect_four::move_string::format_grid;
+use crate::connect_four::service::{Request, Response};
+use crate::connect_four::solver_util::{BOARD_SIZE, DRAW};
+use crate::connect_four::state::State;
+
+pub const DEFAULT_SPLIT_PLY: usize = 4;
+
+#[cfg(unix)]
+const WORKER_START_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(unix)]
+const WORKER_START_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// solving one position with several worker processes. the tree is split a number of plies below
+// the root, every position at that ply is a subproblem solved exactly by a worker, and the root's
+// score is rebuilt from their scores. subproblems that can no longer change the root's score, by
+// the reasoning behind alpha-beta cutoffs, are never handed out
+
+// a worker speaking the JSON-lines protocol of `AnalysisService`, one request at a time
+pub struct WorkerConnection {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+    // servers answer `{"cancel": <id>}`, the plain `serve` loop on stdin does not
+    cancellable: bool,
+    // stops the worker or closes the connection, so that a reader waiting on it sees the end
+    close: Box<dyn FnOnce() + Send>,
+}
+
+impl WorkerConnection {
+    // a process answering requests on its stdin and stdout, such as `serve` without options. it
+    // cannot be cancelled, so a subproblem that is no longer needed keeps it busy until it is solved
+    pub fn spawn(mut command: Command) -> io::Result<Self> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        Ok(Self {
+            reader: Box::new(BufReader::new(stdout)),
+            writer: Box::new(stdin),
+            cancellable: false,
+            // the worker may still be solving a subproblem that is no longer needed
+            close: Box::new(move || {
+                child.kill().ok();
+                child.wait().ok();
+            }),
+        })
+    }
+
+    // a connection to `serve --unix`, which also stops subproblems that are no longer needed
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_unix_stream(UnixStream::connect(path)?)
+    }
+
+    // `command`, such as `serve`, is started with `--unix <path>` and connected to once it listens,
+    // so that it can be cancelled like a server. closing it stops the process and removes the socket
+    #[cfg(unix)]
+    pub fn spawn_unix<P: AsRef<Path>>(mut command: Command, path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut child = command.arg("--unix").arg(&path).stdin(Stdio::null()).stderr(Stdio::null()).spawn()?;
+        let started = Instant::now();
+
+        let connection = loop {
+            match UnixStream::connect(&path).and_then(Self::from_unix_stream) {
+                Ok(connection) => break Ok(connection),
+                Err(_) if started.elapsed() < WORKER_START_TIMEOUT && matches!(child.try_wait(), Ok(None)) => {
+                    thread::sleep(WORKER_START_POLL_INTERVAL);
+                },
+                Err(err) => break Err(err),
+            }
+        };
+
+        let mut stop = move || {
+            child.kill().ok();
+            child.wait().ok();
+            fs::remove_file(&path).ok();
+        };
+
+        match connection {
+            Ok(mut connection) => {
+                let close_stream = connection.close;
+
+                connection.close = Box::new(move || {
+                    close_stream();
+                    stop();
+                });
+
+                Ok(connection)
+            },
+            Err(err) => {
+                stop();
+                Err(err)
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    fn from_unix_stream(stream: UnixStream) -> io::Result<Self> {
+        let reader = stream.try_clone()?;
+        let closer = stream.try_clone()?;
+
+        Ok(Self {
+            reader: Box::new(BufReader::new(reader)),
+            writer: Box::new(stream),
+            cancellable: true,
+            close: Box::new(move || {
+                closer.shutdown(Shutdown::Both).ok();
+            }),
+        })
+    }
+
+    // for workers that were started but will not be used
+    pub fn close(self) {
+        (self.close)();
+    }
+}
+
+pub struct DistributedReturn {
+    pub eval: i32,
+    pub subproblems: usize,
+    // the rest could not change the score, or were stopped once they could not
+    pub solved: usize,
+    // over every worker
+    pub states_evaluated: usize,
+}
+
+// the positions down to the split ply, with transpositions merged
+struct SplitNode {
+    children: Vec<usize>,
+    subproblem: Option<usize>,
+    // bounds on the score from the side to move, equal once the score is known
+    lower: i32,
+    upper: i32,
+}
+
+struct SplitTree<S: State> {
+    // every node comes after its children, so the root is the last one
+    nodes: Vec<SplitNode>,
+    subproblems: Vec<(S, usize)>,
+}
+
+impl<S: State> SplitTree<S> {
+    fn new(root: &S, split_ply: usize) -> Self {
+        let mut tree = Self {
+            nodes: vec![],
+            subproblems: vec![],
+        };
+
+        tree.add_node(root.clone(), split_ply, &mut HashMap::new());
+        tree.update_bounds();
+        tree
+    }
+
+    fn add_node(&mut self, state: S, plies_left: usize, added: &mut HashMap<S, usize>) -> usize {
+        if let Some(&node) = added.get(&state) {
+            return node;
+        }
+
+        let next_states = state.next_states();
+
+        let node = if state.board_full() {
+            SplitNode::exact(DRAW)
+        } else if next_states.iter().any(State::is_win) {
+            SplitNode::exact(state.max_eval())
+        } else if plies_left == 0 {
+            self.subproblems.push((state.clone(), self.nodes.len()));
+            SplitNode::unknown(&state, vec![], Some(self.subproblems.len() - 1))
+        } else {
+            let children = next_states
+                .into_iter()
+                .map(|next_state| self.add_node(next_state, plies_left - 1, added))
+                .collect();
+
+            SplitNode::unknown(&state, children, None)
+        };
+
+        self.nodes.push(node);
+        added.insert(state, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn root(&self) -> &SplitNode {
+        self.nodes.last().expect("the tree has a root")
+    }
+
+    fn solve(&mut self, subproblem: usize, eval: i32) {
+        let node = &mut self.nodes[self.subproblems[subproblem].1];
+        node.lower = eval;
+        node.upper = eval;
+    }
+
+    fn update_bounds(&mut self) {
+        for node in 0..self.nodes.len() {
+            if self.nodes[node].children.is_empty() {
+                continue
+            }
+
+            let (lower, upper) = self.nodes[node].children.iter().fold((i32::MIN, i32::MIN), |(lower, upper), &child| {
+                (max(lower, -self.nodes[child].upper), max(upper, -self.nodes[child].lower))
+            });
+
+            self.nodes[node].lower = lower;
+            self.nodes[node].upper = upper;
+        }
+    }
+
+    // the unsolved subproblems that could still change the root's score, in move order
+    fn open_subproblems(&self) -> Vec<usize> {
+        let root = self.root();
+        let mut open = vec![];
+        self.collect_open(self.nodes.len() - 1, root.lower - 1, root.upper + 1, &mut open);
+        open
+    }
+
+    // a child's score only matters between the best its siblings already guarantee and beta
+    fn collect_open(&self, node: usize, alpha: i32, beta: i32, open: &mut Vec<usize>) {
+        let SplitNode { children, subproblem, lower, upper } = &self.nodes[node];
+
+        if lower >= upper || *lower >= beta || *upper <= alpha {
+            return
+        }
+
+        if let Some(subproblem) = *subproblem {
+            if !open.contains(&subproblem) {
+                open.push(subproblem);
+            }
+
+            return
+        }
+
+        for (index, &child) in children.iter().enumerate() {
+            let siblings_lower = children
+                .iter()
+                .enumerate()
+                .filter(|&(sibling_index, _)| sibling_index != index)
+                .map(|(_, &sibling)| -self.nodes[sibling].upper)
+                .fold(alpha, max);
+
+            if siblings_lower < beta {
+                self.collect_open(child, -beta, -siblings_lower, open);
+            }
+        }
+    }
+}
+
+impl SplitNode {
+    fn exact(eval: i32) -> Self {
+        Self {
+            children: vec![],
+            subproblem: None,
+            lower: eval,
+            upper: eval,
+        }
+    }
+
+    // the opponent's best is a win with their next stone
+    fn unknown<S: State>(state: &S, children: Vec<usize>, subproblem: Option<usize>) -> Self {
+        Self {
+            children,
+            subproblem,
+            lower: -(((BOARD_SIZE - state.moves_made()) / 2) as i32),
+            upper: state.max_eval(),
+        }
+    }
+}
+
+// `solver` is passed on to the workers, which use their default when it is None
+pub fn solve_distributed<S: State>(
+    state: &S,
+    split_ply: usize,
+    solver: Option<&str>,
+    workers: Vec<WorkerConnection>,
+) -> io::Result<DistributedReturn> {
+
+    if workers.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no workers to solve with"));
+    }
+
+    let mut tree = SplitTree::new(state, split_ply);
+    let (replies_sender, replies) = mpsc::channel();
+    let mut writers = vec![];
+    let mut closers = vec![];
+
+    thread::scope(|scope| {
+        for (worker, connection) in workers.into_iter().enumerate() {
+            let replies_sender = replies_sender.clone();
+            scope.spawn(move || forward_replies(worker, connection.reader, replies_sender));
+            writers.push((connection.writer, connection.cancellable));
+            closers.push(connection.close);
+        }
+
+        drop(replies_sender);
+
+        let ret = coordinate(&mut tree, solver, &mut writers, &replies);
+
+        // the readers only finish once their workers are gone
+        for close in closers {
+            close();
+        }
+
+        ret
+    })
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum SubproblemStatus {
+    Waiting,
+    Running,
+    Solved,
+}
+
+fn coordinate<S: State>(
+    tree: &mut SplitTree<S>,
+    solver: Option<&str>,
+    writers: &mut [(Box<dyn Write + Send>, bool)],
+    replies: &Receiver<(usize, io::Result<Response>)>,
+) -> io::Result<DistributedReturn> {
+
+    let mut status = vec![SubproblemStatus::Waiting; tree.subproblems.len()];
+    let mut cancelled = vec![false; tree.subproblems.len()];
+    let mut running: Vec<Option<usize>> = vec![None; writers.len()];
+    let mut solved = 0;
+    let mut states_evaluated = 0;
+
+    while tree.root().lower < tree.root().upper {
+        let open = tree.open_subproblems();
+
+        for (worker, (writer, cancellable)) in writers.iter_mut().enumerate() {
+            if let Some(subproblem) = running[worker]
+                && *cancellable
+                && !cancelled[subproblem]
+                && !open.contains(&subproblem)
+            {
+                writeln!(writer, "{}", json!({ "cancel": subproblem }))?;
+                writer.flush()?;
+                cancelled[subproblem] = true;
+            }
+        }
+
+        let mut waiting = open
+            .into_iter()
+            .filter(|&subproblem| status[subproblem] == SubproblemStatus::Waiting)
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        for (worker, (writer, _)) in writers.iter_mut().enumerate() {
+            if running[worker].is_some() {
+                continue
+            }
+
+            let Some(subproblem) = waiting.next() else {
+                break
+            };
+
+            let request = Request {
+                id: Value::from(subproblem),
+                position: format_grid(&tree.subproblems[subproblem].0),
+                solver: solver.map(str::to_string),
+                ..Request::default()
+            };
+
+            writeln!(writer, "{}", serde_json::to_string(&request).expect("requests are always serializable"))?;
+            writer.flush()?;
+
+            running[worker] = Some(subproblem);
+            status[subproblem] = SubproblemStatus::Running;
+        }
+
+        if running.iter().all(Option::is_none) {
+            return Err(io::Error::other("no subproblem left to decide the score"));
+        }
+
+        let (worker, reply) = replies.recv().map_err(|_| io::Error::other("every worker stopped"))?;
+        let reply = reply.map_err(|err| io::Error::new(err.kind(), format!("worker {}: {err}", worker + 1)))?;
+
+        let replied_to = reply.id.as_u64().map(|id| id as usize);
+
+        // a cancel that arrives just as its request finishes is answered with an error of its own,
+        // before or after the request's reply
+        if !reply.ok && replied_to.is_some_and(|id| id < cancelled.len() && cancelled[id]) {
+            continue
+        }
+
+        if replied_to.is_none() || replied_to != running[worker] {
+            return Err(io::Error::other(format!("worker {} answered a request it was not sent", worker + 1)));
+        }
+
+        let subproblem = running[worker].take().expect("the reply matches the running subproblem");
+
+        if !reply.ok {
+            let error = reply.error.unwrap_or_default();
+            return Err(io::Error::other(format!("worker {}: {error}", worker + 1)));
+        }
+
+        states_evaluated += reply.nodes;
+
+        match (reply.eval, reply.exact) {
+            (Some(eval), Some(true)) => {
+                tree.solve(subproblem, eval);
+                tree.update_bounds();
+                status[subproblem] = SubproblemStatus::Solved;
+                solved += 1;
+            },
+            _ if reply.cancelled => status[subproblem] = SubproblemStatus::Waiting,
+            _ => return Err(io::Error::other(format!("worker {} did not solve its subproblem exactly", worker + 1))),
+        }
+    }
+
+    Ok(DistributedReturn {
+        eval: tree.root().lower,
+        subproblems: tree.subproblems.len(),
+        solved,
+        states_evaluated,
+    })
+}
+
+// passes each reply on to the coordinator until the worker stops or sends something unreadable
+fn forward_replies(worker: usize, reader: Box<dyn BufRead + Send>, replies: Sender<(usize, io::Result<Response>)>) {
+    for line in reader.lines() {
+        let reply = line.and_then(|line| serde_json::from_str(&line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)));
+        let failed = reply.is_err();
+
+        if replies.send((worker, reply)).is_err() || failed {
+            return
+        }
+    }
+
+    replies.send((worker, Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the worker stopped")))).ok();
+}
@@ -0,0 +1,301 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
+use crate::connect_four::budget::{CancelToken, SearchBudget, SharedBudget};
+use crate::connect_four::depth_limited;
+use crate::connect_four::heuristic::StaticEvaluation;
+use crate::connect_four::move_string::{format_moves, parse_moves, parse_position, play_moves};
+use crate::connect_four::player::legal_moves;
+use crate::connect_four::solver::{scored_columns, CachingSession, Solver, SolverConfig};
+use crate::connect_four::solver_util::{BOARD_SIZE, COLS};
+
+pub const ENGINE_NAME: &str = "software_testing_project";
+
+// a quick heuristic search runs first so there is always a move to play when the exact search is stopped
+const HEURISTIC_DEPTH: usize = 8;
+const INFINITE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GoOptions {
+    pub movetime: Option<Duration>,
+    pub nodes: Option<usize>,
+    pub infinite: bool,
+}
+
+impl GoOptions {
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        let mut options = GoOptions::default();
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            match arg {
+                "movetime" => options.movetime = Some(Duration::from_millis(args.next()?.parse().ok()?)),
+                "nodes" => options.nodes = Some(args.next()?.parse().ok()?),
+                "infinite" => options.infinite = true,
+                _ => return None,
+            }
+        }
+
+        Some(options)
+    }
+}
+
+struct SearchInfo {
+    depth: usize,
+    score: i32,
+    exact: bool,
+    nodes: usize,
+    elapsed: Duration,
+    pv: Vec<usize>,
+}
+
+impl SearchInfo {
+    fn line(&self) -> String {
+        let millis = self.elapsed.as_millis();
+        let nps = (self.nodes as f64 / self.elapsed.as_secs_f64().max(1e-9)) as u64;
+        let score = if self.exact { "exact" } else { "cp" };
+
+        format!(
+            "info depth {} score {score} {} nodes {} time {millis} nps {nps} pv {}",
+            self.depth,
+            self.score,
+            self.nodes,
+            self.pv.iter().map(|col| (col + 1).to_string()).collect::<Vec<_>>().join(" "),
+        )
+    }
+}
+
+pub struct Engine<S: StaticEvaluation> {
+    state: S,
+    solver: CachingSession<S>,
+}
+
+impl<S: StaticEvaluation> Engine<S> {
+    pub fn new() -> Self {
+        Self {
+            state: S::start_state(),
+            solver: CachingSession::new(SolverConfig::default()),
+        }
+    }
+
+    // reads commands until `quit` or the end of the input; `go` searches on a separate thread,
+    // so `stop` and `isready` are answered while it runs
+    pub fn run<R: BufRead, W: Write + Send>(&mut self, input: R, output: W) -> io::Result<()> {
+        let output = Mutex::new(output);
+
+        thread::scope(|scope| {
+            let mut search: Option<RunningSearch<'_>> = None;
+
+            for line in input.lines() {
+                let line = line?;
+                let words: Vec<&str> = line.split_whitespace().collect();
+
+                let Some((&command, args)) = words.split_first() else {
+                    continue
+                };
+
+                // only `stop` and `quit` interrupt a search; other commands that change the
+                // engine's state wait for it, so a piped script runs each search to its end
+                if matches!(command, "go" | "position" | "ucinewgame" | "stop" | "quit")
+                    && let Some(running) = search.take()
+                {
+                    running.finish(matches!(command, "stop" | "quit"))?;
+                }
+
+                match command {
+                    "uci" => send(&output, &[&format!("id name {ENGINE_NAME}"), "uciok"])?,
+                    "isready" => send(&output, &["readyok"])?,
+                    "ucinewgame" => {
+                        self.solver.clear_cache();
+                        self.state = S::start_state();
+                    },
+                    "position" => match parse_position_command(args) {
+                        Some(state) => self.state = state,
+                        None => send(&output, &[&format!("info string invalid position \"{}\"", args.join(" "))])?,
+                    },
+                    "go" => match GoOptions::parse(args) {
+                        Some(options) => {
+                            let cancel_token = CancelToken::new();
+                            let state = self.state.clone();
+                            let solver = &self.solver;
+                            let output = &output;
+                            let search_token = cancel_token.clone();
+
+                            let infinite = options.infinite;
+                            let handle = scope.spawn(move || search_position(&state, solver, &options, &search_token, output));
+
+                            search = Some(RunningSearch {
+                                cancel_token,
+                                infinite,
+                                handle,
+                            });
+                        },
+                        None => send(&output, &[&format!("info string invalid go options \"{}\"", args.join(" "))])?,
+                    },
+                    "stop" => {},
+                    "quit" => return Ok(()),
+                    _ => send(&output, &[&format!("info string unknown command \"{command}\"")])?,
+                }
+            }
+
+            // at the end of the input only an infinite search, which would never end, is stopped
+            if let Some(running) = search.take() {
+                let infinite = running.infinite;
+                running.finish(infinite)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl<S: StaticEvaluation> Default for Engine<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct RunningSearch<'scope> {
+    cancel_token: CancelToken,
+    infinite: bool,
+    handle: ScopedJoinHandle<'scope, io::Result<()>>,
+}
+
+impl RunningSearch<'_> {
+    fn finish(self, stop: bool) -> io::Result<()> {
+        if stop {
+            self.cancel_token.cancel();
+        }
+
+        self.handle.join().unwrap()
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, lines: &[&str]) -> io::Result<()> {
+    let mut output = output.lock().unwrap();
+
+    for line in lines {
+        writeln!(output, "{line}")?;
+    }
+
+    output.flush()
+}
+
+// `position startpos`, `position startpos moves 4453`, `position moves 4453` or `position grid <rows>`
+fn parse_position_command<S: StaticEvaluation>(args: &[&str]) -> Option<S> {
+    match args {
+        ["startpos"] => Some(S::start_state()),
+        ["startpos", "moves", moves] | ["moves", moves] => play_moves(&parse_moves(moves)?),
+        ["grid", grid] => parse_position(grid),
+        _ => None,
+    }
+}
+
+fn search_position<S: StaticEvaluation, W: Write>(
+    state: &S,
+    solver: &CachingSession<S>,
+    options: &GoOptions,
+    cancel_token: &CancelToken,
+    output: &Mutex<W>,
+) -> io::Result<()> {
+
+    let start = Instant::now();
+    let mut budget = SearchBudget::unlimited().with_cancel_token(cancel_token.clone());
+
+    if let Some(movetime) = options.movetime {
+        budget = budget.with_time_limit(movetime);
+    }
+
+    if let Some(max_nodes) = options.nodes {
+        budget = budget.with_max_nodes(max_nodes);
+    }
+
+    // the heuristic search, the exact search and the principal variation all draw on one budget
+    let mut budget = budget.start_shared();
+    let mut best_move = if state.is_win() { None } else { legal_moves(state).first().copied() };
+
+    if best_move.is_some() {
+        let result = depth_limited::search_with_budget(state, HEURISTIC_DEPTH, &budget.remaining());
+        budget.spend(result.states_evaluated);
+        best_move = result.best_move.or(best_move);
+
+        let info = SearchInfo {
+            depth: result.depth,
+            score: result.proven_eval().unwrap_or(result.score),
+            exact: result.proven_eval().is_some(),
+            nodes: result.states_evaluated,
+            elapsed: start.elapsed(),
+            pv: best_move.into_iter().collect(),
+        };
+
+        send(output, &[&info.line()])?;
+
+        let config = SolverConfig::default().with_budget(budget.remaining());
+        let exact = solver.evaluate_with_config(state.clone(), &config);
+        budget.spend(exact.states_evaluated);
+
+        if exact.is_exact() {
+            let pv = principal_variation(state, exact.eval, solver, &mut budget);
+            best_move = pv.first().copied().or(best_move);
+
+            let info = SearchInfo {
+                depth: BOARD_SIZE - state.moves_made(),
+                score: exact.eval,
+                exact: true,
+                nodes: budget.spent(),
+                elapsed: start.elapsed(),
+                pv,
+            };
+
+            send(output, &[&info.line()])?;
+        }
+    }
+
+    // in infinite mode the best move is only sent once the search is stopped
+    while options.infinite && !cancel_token.is_cancelled() {
+        thread::sleep(INFINITE_POLL_INTERVAL);
+    }
+
+    let best_move = best_move.map_or("none".to_string(), |col| format_moves(&[col]));
+    send(output, &[&format!("bestmove {best_move}")])
+}
+
+// follows a move keeping the score at every ply until the game ends or the budget runs out
+fn principal_variation<S: StaticEvaluation>(
+    state: &S,
+    mut score: i32,
+    solver: &CachingSession<S>,
+    budget: &mut SharedBudget,
+) -> Vec<usize> {
+
+    let mut pv = vec![];
+    let mut state = state.clone();
+
+    while !state.is_win() && !state.board_full() {
+        // the solver may have proven the score from a win alone, and scoring the columns before it
+        // in move order could take far longer than that proof
+        if let Some(col) = (0..COLS).find(|&col| state.play_move(col).is_some_and(|next_state| next_state.is_win())) {
+            pv.push(col);
+            break;
+        }
+
+        let config = SolverConfig::default().with_budget(budget.remaining());
+        let column = scored_columns(solver, &state, &config)
+            .inspect(|column| budget.spend(column.states_evaluated))
+            .find(|column| !column.exact || column.score == score);
+
+        // a bound could match the score by chance, so the variation ends at the first inexact search
+        let Some(column) = column.filter(|column| column.exact) else {
+            break;
+        };
+
+        pv.push(column.col);
+        state = state.play_move(column.col).unwrap();
+        score = -score;
+    }
+
+    pv
+}
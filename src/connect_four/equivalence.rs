@@ -0,0 +1,169 @@
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::connect_four::move_string::format_moves;
+use crate::connect_four::solver::{SolverConfig, SolverRegistry};
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+use crate::connect_four::state_array::StateArray;
+use crate::connect_four::state_bitboard::StateBitboard;
+
+pub struct Divergence {
+    pub moves: Vec<usize>,
+    pub reason: String,
+    pub array_board: String,
+    pub bitboard_board: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} after moves \"{}\"", self.reason, format_moves(&self.moves))?;
+        writeln!(f, "array:")?;
+        write!(f, "{}", self.array_board)?;
+        writeln!(f, "bitboard:")?;
+        write!(f, "{}", self.bitboard_board)
+    }
+}
+
+fn divergence(moves: &[usize], reason: String, array: &StateArray, bitboard: &StateBitboard) -> Divergence {
+    Divergence {
+        moves: moves.to_vec(),
+        reason,
+        array_board: array.decode(),
+        bitboard_board: bitboard.decode(),
+    }
+}
+
+fn compare<T: PartialEq + fmt::Debug>(
+    moves: &[usize],
+    property: &str,
+    array_value: T,
+    bitboard_value: T,
+    array: &StateArray,
+    bitboard: &StateBitboard,
+) -> Result<(), Divergence> {
+
+    if array_value != bitboard_value {
+        return Err(divergence(
+            moves,
+            format!("{property}: array {array_value:?}, bitboard {bitboard_value:?}"),
+            array,
+            bitboard,
+        ));
+    }
+
+    Ok(())
+}
+
+fn legal_moves<S: State>(state: &S) -> Vec<usize> {
+    (0..COLS).filter(|&col| state.play_move(col).is_some()).collect()
+}
+
+pub fn compare_states(moves: &[usize], array: &StateArray, bitboard: &StateBitboard) -> Result<(), Divergence> {
+    compare(moves, "is_win", array.is_win(), bitboard.is_win(), array, bitboard)?;
+    compare(moves, "board_full", array.board_full(), bitboard.board_full(), array, bitboard)?;
+    compare(moves, "moves_made", array.moves_made(), bitboard.moves_made(), array, bitboard)?;
+    compare(moves, "max_eval", array.max_eval(), bitboard.max_eval(), array, bitboard)?;
+    compare(moves, "decode", array.decode(), bitboard.decode(), array, bitboard)?;
+    compare(moves, "legal moves", legal_moves(array), legal_moves(bitboard), array, bitboard)
+}
+
+// plays `moves` on both representations in lockstep, comparing them after every move
+pub fn check_game(moves: &[usize]) -> Result<(), Divergence> {
+    let mut array = StateArray::start_state();
+    let mut bitboard = StateBitboard::start_state();
+
+    compare_states(&[], &array, &bitboard)?;
+
+    for (ply, &col) in moves.iter().enumerate() {
+        let played = &moves[..=ply];
+
+        match (array.play_move(col), bitboard.play_move(col)) {
+            (Some(next_array), Some(next_bitboard)) => {
+                array = next_array;
+                bitboard = next_bitboard;
+            },
+            (None, None) => return Ok(()),
+            (next_array, next_bitboard) => return Err(divergence(
+                played,
+                format!("play_move: array legal {}, bitboard legal {}", next_array.is_some(), next_bitboard.is_some()),
+                &array,
+                &bitboard,
+            )),
+        }
+
+        compare_states(played, &array, &bitboard)?;
+    }
+
+    Ok(())
+}
+
+pub fn random_game(rng: &mut Pcg64) -> Vec<usize> {
+    let mut state = StateBitboard::start_state();
+    let mut moves = vec![];
+
+    while !state.is_win() && !state.board_full() {
+        let legal = legal_moves(&state);
+        let col = legal[rng.random_range(0..legal.len())];
+
+        state = state.play_move(col).unwrap();
+        moves.push(col);
+    }
+
+    moves
+}
+
+// returns the number of positions compared
+pub fn check_random_games(seed: u64, games: usize) -> Result<usize, Divergence> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut positions = 0;
+
+    for _ in 0..games {
+        let moves = random_game(&mut rng);
+        check_game(&moves)?;
+        positions += moves.len() + 1;
+    }
+
+    Ok(positions)
+}
+
+// solves every non-terminal position of each random game that has at least
+// `min_moves_made` moves with every registered solver on both representations
+pub fn check_solvers(seed: u64, games: usize, min_moves_made: usize) -> Result<usize, Divergence> {
+    let array_solvers = SolverRegistry::<StateArray>::with_default_solvers().create_all(SolverConfig::default());
+    let bitboard_solvers = SolverRegistry::<StateBitboard>::with_default_solvers().create_all(SolverConfig::default());
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut positions = 0;
+
+    for _ in 0..games {
+        let moves = random_game(&mut rng);
+        let mut array = StateArray::start_state();
+        let mut bitboard = StateBitboard::start_state();
+
+        for (ply, &col) in moves.iter().enumerate() {
+            array = array.play_move(col).unwrap();
+            bitboard = bitboard.play_move(col).unwrap();
+
+            if ply + 1 < min_moves_made || array.is_win() || array.board_full() {
+                continue;
+            }
+
+            let played = &moves[..=ply];
+            let mut scores = vec![];
+
+            for (array_solver, bitboard_solver) in array_solvers.iter().zip(&bitboard_solvers) {
+                scores.push((format!("{} array", array_solver.name()), array_solver.evaluate(array.clone()).eval));
+                scores.push((format!("{} bitboard", bitboard_solver.name()), bitboard_solver.evaluate(bitboard.clone()).eval));
+            }
+
+            if scores.iter().any(|(_, score)| *score != scores[0].1) {
+                return Err(divergence(played, format!("solver scores: {scores:?}"), &array, &bitboard));
+            }
+
+            positions += 1;
+        }
+    }
+
+    Ok(positions)
+}
@@ -0,0 +1,174 @@
+use std::ffi::{c_char, c_int, CStr};
+use std::time::Duration;
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::move_string::parse_position;
+use crate::connect_four::solver::{CachingSession, Solver, SolverConfig};
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+use crate::connect_four::state_bitboard::StateBitboard;
+
+// a C interface to a caching solver session, declared in include/connect_four.h.
+// every function returns one of the C4_* codes below and writes its results through out pointers
+
+pub const C4_OK: c_int = 0;
+pub const C4_ERROR_NULL_POINTER: c_int = 1;
+pub const C4_ERROR_INVALID_POSITION: c_int = 2;
+pub const C4_ERROR_GAME_OVER: c_int = 3;
+
+// the score of a column that cannot be played
+pub const C4_FULL_COLUMN: i32 = i32::MIN;
+
+// a session keeps its cache between calls, so analysing the positions of one game gets faster as it goes
+pub struct C4Session {
+    solver: CachingSession<StateBitboard>,
+    state: StateBitboard,
+}
+
+// zero means no limit; the limits cover the whole call however many searches it takes
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct C4Budget {
+    pub time_ms: u64,
+    pub max_nodes: u64,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct C4Evaluation {
+    // from the perspective of the player to move
+    pub score: i32,
+    // false when the budget ran out, in which case the score is only a bound
+    pub exact: bool,
+    pub nodes: u64,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct C4ColumnScores {
+    // indexed from the leftmost column, C4_FULL_COLUMN for full columns
+    pub scores: [i32; COLS],
+    // index into `scores`, or -1 when no column can be played
+    pub best_move: c_int,
+    pub exact: bool,
+    pub nodes: u64,
+}
+
+impl C4Budget {
+    fn config(&self) -> SolverConfig {
+        let mut budget = SearchBudget::unlimited();
+
+        if self.time_ms > 0 {
+            budget = budget.with_time_limit(Duration::from_millis(self.time_ms));
+        }
+
+        if self.max_nodes > 0 {
+            budget = budget.with_max_nodes(self.max_nodes as usize);
+        }
+
+        SolverConfig::default().with_budget(budget)
+    }
+}
+
+impl C4Session {
+    fn playable_state(&self) -> Result<&StateBitboard, c_int> {
+        if self.state.is_win() || self.state.board_full() {
+            Err(C4_ERROR_GAME_OVER)
+        } else {
+            Ok(&self.state)
+        }
+    }
+}
+
+/// Creates a session at the starting position. Free it with `c4_session_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn c4_session_new() -> *mut C4Session {
+    Box::into_raw(Box::new(C4Session {
+        solver: CachingSession::new(SolverConfig::default()),
+        state: StateBitboard::start_state(),
+    }))
+}
+
+/// # Safety
+/// `session` must be null or a pointer from `c4_session_new` that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn c4_session_free(session: *mut C4Session) {
+    if !session.is_null() {
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
+
+/// Sets the position from a move string of 1-based columns such as "4453", or from a grid like
+/// `move_string::parse_position` accepts. The position is left unchanged when it is invalid.
+///
+/// # Safety
+/// `session` must come from `c4_session_new` and `position` must be a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn c4_session_set_position(session: *mut C4Session, position: *const c_char) -> c_int {
+    let Some(session) = (unsafe { session.as_mut() }) else {
+        return C4_ERROR_NULL_POINTER
+    };
+
+    if position.is_null() {
+        return C4_ERROR_NULL_POINTER
+    }
+
+    let Some(state) = unsafe { CStr::from_ptr(position) }.to_str().ok().and_then(parse_position) else {
+        return C4_ERROR_INVALID_POSITION
+    };
+
+    session.state = state;
+    C4_OK
+}
+
+/// Scores the current position.
+///
+/// # Safety
+/// `session` must come from `c4_session_new` and `out` must point to a writable `C4Evaluation`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn c4_session_evaluate(session: *const C4Session, budget: C4Budget, out: *mut C4Evaluation) -> c_int {
+    let (Some(session), Some(out)) = (unsafe { session.as_ref() }, unsafe { out.as_mut() }) else {
+        return C4_ERROR_NULL_POINTER
+    };
+
+    let state = match session.playable_state() {
+        Ok(state) => state.clone(),
+        Err(code) => return code,
+    };
+
+    let ret = session.solver.evaluate_with_config(state, &budget.config());
+
+    *out = C4Evaluation {
+        score: ret.eval,
+        exact: ret.is_exact(),
+        nodes: ret.states_evaluated as u64,
+    };
+
+    C4_OK
+}
+
+/// Scores every column of the current position and picks the best one.
+///
+/// # Safety
+/// `session` must come from `c4_session_new` and `out` must point to a writable `C4ColumnScores`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn c4_session_column_scores(session: *const C4Session, budget: C4Budget, out: *mut C4ColumnScores) -> c_int {
+    let (Some(session), Some(out)) = (unsafe { session.as_ref() }, unsafe { out.as_mut() }) else {
+        return C4_ERROR_NULL_POINTER
+    };
+
+    let state = match session.playable_state() {
+        Ok(state) => state,
+        Err(code) => return code,
+    };
+
+    let scores = session.solver.column_scores_with_config(state, &budget.config());
+
+    *out = C4ColumnScores {
+        scores: scores.scores.map(|score| score.unwrap_or(C4_FULL_COLUMN)),
+        best_move: scores.best_column().map_or(-1, |col| col as c_int),
+        exact: scores.is_exact(),
+        nodes: scores.states_evaluated as u64,
+    };
+
+    C4_OK
+}
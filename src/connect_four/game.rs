@@ -0,0 +1,200 @@
+use std::fmt;
+use crate::connect_four::solver_util::{COLS, EMPTY_CELL, FIRST_PLAYER, ROWS, SECOND_PLAYER};
+use crate::connect_four::state::State;
+
+const LINE_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GameStatus {
+    InProgress,
+    Won(char),
+    Draw,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MoveError {
+    GameOver,
+    InvalidColumn,
+    ColumnFull,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "the game is over"),
+            MoveError::InvalidColumn => write!(f, "columns are numbered 1 to {COLS}"),
+            MoveError::ColumnFull => write!(f, "that column is full"),
+        }
+    }
+}
+
+// a game in progress with its move history, so moves can be taken back
+pub struct Game<S: State> {
+    states: Vec<S>,
+    moves: Vec<usize>,
+}
+
+impl<S: State> Game<S> {
+    pub fn new() -> Self {
+        Self {
+            states: vec![S::start_state()],
+            moves: vec![],
+        }
+    }
+
+    pub fn from_moves(moves: &[usize]) -> Result<Self, MoveError> {
+        let mut game = Self::new();
+
+        for &col in moves {
+            game.play(col)?;
+        }
+
+        Ok(game)
+    }
+
+    pub fn state(&self) -> &S {
+        self.states.last().unwrap()
+    }
+
+    pub fn moves(&self) -> &[usize] {
+        &self.moves
+    }
+
+    pub fn player_to_move(&self) -> char {
+        if (self.state().moves_made() & 1) == 0 { FIRST_PLAYER } else { SECOND_PLAYER }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        let state = self.state();
+
+        if state.is_win() {
+            GameStatus::Won(if (state.moves_made() & 1) == 1 { FIRST_PLAYER } else { SECOND_PLAYER })
+        } else if state.board_full() {
+            GameStatus::Draw
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    pub fn play(&mut self, col: usize) -> Result<(), MoveError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(MoveError::GameOver);
+        }
+
+        if col >= COLS {
+            return Err(MoveError::InvalidColumn);
+        }
+
+        let next_state = self.state().play_move(col).ok_or(MoveError::ColumnFull)?;
+        self.states.push(next_state);
+        self.moves.push(col);
+
+        Ok(())
+    }
+
+    // returns the column of the move taken back
+    pub fn undo(&mut self) -> Option<usize> {
+        let col = self.moves.pop()?;
+        self.states.pop();
+        Some(col)
+    }
+
+    // rows are counted from the top, as in `State::decode`
+    pub fn last_move(&self) -> Option<(usize, usize)> {
+        let col = *self.moves.last()?;
+        let grid = self.grid();
+        let row = (0..ROWS).find(|&row| grid[row][col] != EMPTY_CELL)?;
+
+        Some((row, col))
+    }
+
+    // every cell of the lines of four or more through the last move, if it won the game
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        if !self.state().is_win() {
+            return None
+        }
+
+        let (row, col) = self.last_move()?;
+        let grid = self.grid();
+        let piece = grid[row][col];
+        let mut cells = vec![];
+
+        for (dr, dc) in LINE_DIRECTIONS {
+            let mut line = vec![(row, col)];
+
+            for sign in [1, -1] {
+                let (mut r, mut c) = (row as i32 + sign * dr, col as i32 + sign * dc);
+
+                while (0..ROWS as i32).contains(&r) && (0..COLS as i32).contains(&c) && grid[r as usize][c as usize] == piece {
+                    line.push((r as usize, c as usize));
+                    r += sign * dr;
+                    c += sign * dc;
+                }
+            }
+
+            if line.len() >= 4 {
+                cells.extend(line);
+            }
+        }
+
+        cells.sort_unstable();
+        cells.dedup();
+        Some(cells)
+    }
+
+    // the last move is drawn in parentheses and the winning line in brackets
+    pub fn render(&self) -> String {
+        let grid = self.grid();
+        let last_move = self.last_move();
+        let winning_line = self.winning_line().unwrap_or_default();
+        let mut rendered = String::new();
+
+        for col in 0..COLS {
+            rendered.push_str(&format!(" {} ", col + 1));
+        }
+
+        rendered.push('\n');
+
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                let cell = if cell == EMPTY_CELL { '.' } else { cell };
+
+                if winning_line.contains(&(row, col)) {
+                    rendered.push_str(&format!("[{cell}]"));
+                } else if last_move == Some((row, col)) {
+                    rendered.push_str(&format!("({cell})"));
+                } else {
+                    rendered.push_str(&format!(" {cell} "));
+                }
+            }
+
+            rendered.push('\n');
+        }
+
+        rendered
+    }
+
+    fn grid(&self) -> Vec<Vec<char>> {
+        self.state()
+            .decode()
+            .lines()
+            .map(|line| {
+                let mut cells: Vec<char> = line.chars().collect();
+                cells.resize(COLS, EMPTY_CELL);
+                cells
+            })
+            .collect()
+    }
+}
+
+impl<S: State> Default for Game<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: State> fmt::Display for Game<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
@@ -0,0 +1,426 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use crate::connect_four::solver_util::{COLS, ROWS};
+use crate::connect_four::state::State;
+
+const MAX_LINE_WIDTH: usize = 80;
+const EVAL_TAG: &str = "[%eval ";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GameResult {
+    FirstPlayerWin,
+    SecondPlayerWin,
+    Draw,
+    Unknown,
+}
+
+impl GameResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameResult::FirstPlayerWin => "1-0",
+            GameResult::SecondPlayerWin => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(GameResult::FirstPlayerWin),
+            "0-1" => Some(GameResult::SecondPlayerWin),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Unknown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedMove {
+    pub col: usize,
+    pub eval: Option<i32>,
+    pub comment: Option<String>,
+}
+
+impl RecordedMove {
+    pub fn new(col: usize) -> Self {
+        Self {
+            col,
+            eval: None,
+            comment: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameRecord {
+    pub first_player: String,
+    pub second_player: String,
+    pub date: String,
+    pub result: GameResult,
+    pub extra_headers: Vec<(String, String)>,
+    pub moves: Vec<RecordedMove>,
+}
+
+#[derive(Debug)]
+pub struct GameRecordError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl GameRecordError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+
+    fn without_line(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GameRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for GameRecordError {}
+
+impl GameRecord {
+    pub fn new(first_player: &str, second_player: &str) -> Self {
+        Self {
+            first_player: first_player.to_string(),
+            second_player: second_player.to_string(),
+            date: "????.??.??".to_string(),
+            result: GameResult::Unknown,
+            extra_headers: vec![],
+            moves: vec![],
+        }
+    }
+
+    pub fn from_moves(first_player: &str, second_player: &str, moves: &[usize]) -> Self {
+        let mut record = Self::new(first_player, second_player);
+        record.moves = moves.iter().map(|&col| RecordedMove::new(col)).collect();
+        record
+    }
+
+    pub fn columns(&self) -> Vec<usize> {
+        self.moves.iter().map(|recorded_move| recorded_move.col).collect()
+    }
+
+    // the state before the first move followed by the state after every ply
+    pub fn replay<S: State>(&self) -> Result<Vec<S>, GameRecordError> {
+        let mut states = vec![S::start_state()];
+
+        for (ply, recorded_move) in self.moves.iter().enumerate() {
+            let state = states.last().unwrap();
+
+            if state.is_win() {
+                return Err(GameRecordError::without_line(format!("move {} is played after the game ended", ply + 1)));
+            }
+
+            let Some(next_state) = state.play_move(recorded_move.col) else {
+                return Err(GameRecordError::without_line(format!("move {} plays into full column {}", ply + 1, recorded_move.col + 1)));
+            };
+
+            states.push(next_state);
+        }
+
+        Ok(states)
+    }
+
+    // the game's result as implied by the moves alone
+    pub fn played_result<S: State>(&self) -> Result<GameResult, GameRecordError> {
+        let last = self.replay::<S>()?.pop().unwrap();
+
+        Ok(if last.is_win() {
+            if (last.moves_made() & 1) == 1 { GameResult::FirstPlayerWin } else { GameResult::SecondPlayerWin }
+        } else if last.board_full() {
+            GameResult::Draw
+        } else {
+            GameResult::Unknown
+        })
+    }
+
+    pub fn render_replay<S: State>(&self) -> Result<String, GameRecordError> {
+        let mut rendered = String::new();
+
+        for (ply, state) in self.replay::<S>()?.iter().enumerate() {
+            if ply == 0 {
+                rendered.push_str("start\n");
+            } else {
+                let recorded_move = &self.moves[ply - 1];
+                rendered.push_str(&format!("{}. column {}", ply, recorded_move.col + 1));
+
+                if let Some(eval) = recorded_move.eval {
+                    rendered.push_str(&format!(" ({eval:+})"));
+                }
+
+                if let Some(comment) = &recorded_move.comment {
+                    rendered.push_str(&format!(" {comment}"));
+                }
+
+                rendered.push('\n');
+            }
+
+            rendered.push_str(&state.decode());
+        }
+
+        Ok(rendered)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// a leading [ would read back as the start of an eval tag
+fn escape_comment(comment: &str) -> String {
+    let escaped = comment.replace('\\', "\\\\").replace('}', "\\}");
+
+    match escaped.strip_prefix('[') {
+        Some(rest) => format!("\\[{rest}"),
+        None => escaped,
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next().unwrap_or('\\') } else { c });
+    }
+
+    unescaped
+}
+
+fn write_header(f: &mut fmt::Formatter<'_>, name: &str, value: &str) -> fmt::Result {
+    writeln!(f, "[{name} \"{}\"]", escape(value))
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_header(f, "First", &self.first_player)?;
+        write_header(f, "Second", &self.second_player)?;
+        write_header(f, "Date", &self.date)?;
+        write_header(f, "Result", self.result.as_str())?;
+        write_header(f, "Board", &format!("{COLS}x{ROWS}"))?;
+
+        for (name, value) in &self.extra_headers {
+            write_header(f, name, value)?;
+        }
+
+        writeln!(f)?;
+
+        let mut tokens = vec![];
+
+        for (ply, recorded_move) in self.moves.iter().enumerate() {
+            if (ply & 1) == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+
+            tokens.push((recorded_move.col + 1).to_string());
+
+            let comment = match (recorded_move.eval, &recorded_move.comment) {
+                (Some(eval), Some(comment)) => Some(format!("{{{EVAL_TAG}{eval:+}] {}}}", escape_comment(comment))),
+                (Some(eval), None) => Some(format!("{{{EVAL_TAG}{eval:+}]}}")),
+                (None, Some(comment)) => Some(format!("{{{}}}", escape_comment(comment))),
+                (None, None) => None,
+            };
+
+            tokens.extend(comment);
+        }
+
+        tokens.push(self.result.as_str().to_string());
+
+        let mut line_width = 0;
+
+        for token in tokens {
+            if line_width > 0 && line_width + 1 + token.len() > MAX_LINE_WIDTH {
+                writeln!(f)?;
+                line_width = 0;
+            } else if line_width > 0 {
+                write!(f, " ")?;
+                line_width += 1;
+            }
+
+            write!(f, "{token}")?;
+            line_width += token.len();
+        }
+
+        writeln!(f)
+    }
+}
+
+fn parse_header(line: &str, line_number: usize) -> Result<(String, String), GameRecordError> {
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| GameRecordError::new(line_number, "malformed header"))?;
+
+    let (name, quoted) = inner
+        .split_once(' ')
+        .ok_or_else(|| GameRecordError::new(line_number, "header has no value"))?;
+
+    let quoted = quoted
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| GameRecordError::new(line_number, "header value is not quoted"))?;
+
+    Ok((name.to_string(), unescape(quoted)))
+}
+
+// the comment's text with escapes still in it, so that an escaped [ is not taken for an eval tag
+fn parse_comment(text: &str) -> (Option<i32>, Option<String>) {
+    let text = text.trim();
+
+    if let Some(rest) = text.strip_prefix(EVAL_TAG)
+        && let Some((eval, comment)) = rest.split_once(']')
+        && let Ok(eval) = eval.trim().parse()
+    {
+        let comment = comment.trim();
+        return (Some(eval), (!comment.is_empty()).then(|| unescape(comment)));
+    }
+
+    (None, (!text.is_empty()).then(|| unescape(text)))
+}
+
+// the first } that is not escaped with a backslash
+fn find_comment_end(text: &str) -> Option<usize> {
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '}' => return Some(index),
+            _ => {},
+        }
+    }
+
+    None
+}
+
+impl FromStr for GameRecord {
+    type Err = GameRecordError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut record = GameRecord::new("?", "?");
+        let mut header_result = None;
+        let mut lines = text.lines().enumerate().peekable();
+
+        while let Some((index, line)) = lines.next_if(|(_, line)| line.trim().is_empty() || line.trim_start().starts_with('[')) {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = parse_header(line, index + 1)?;
+
+            match name.as_str() {
+                "First" => record.first_player = value,
+                "Second" => record.second_player = value,
+                "Date" => record.date = value,
+                "Result" => {
+                    header_result = Some(GameResult::parse(&value)
+                        .ok_or_else(|| GameRecordError::new(index + 1, format!("unknown result \"{value}\"")))?);
+                },
+                "Board" => {
+                    if value != format!("{COLS}x{ROWS}") {
+                        return Err(GameRecordError::new(index + 1, format!("unsupported board size \"{value}\"")));
+                    }
+                },
+                _ => record.extra_headers.push((name, value)),
+            }
+        }
+
+        let mut movetext_result = None;
+        let mut comment: Option<(usize, String)> = None;
+
+        for (index, line) in lines {
+            let mut rest = line;
+
+            while !rest.is_empty() {
+                if let Some((start, text)) = &mut comment {
+                    match find_comment_end(rest).map(|end| (&rest[..end], &rest[end + 1..])) {
+                        Some((end, after)) => {
+                            text.push_str(end);
+
+                            let Some(last_move) = record.moves.last_mut() else {
+                                return Err(GameRecordError::new(*start, "comment before the first move"));
+                            };
+
+                            (last_move.eval, last_move.comment) = parse_comment(text);
+                            comment = None;
+                            rest = after;
+                        },
+                        None => {
+                            text.push_str(rest);
+                            text.push(' ');
+                            rest = "";
+                        },
+                    }
+
+                    continue;
+                }
+
+                rest = rest.trim_start();
+
+                if let Some(after) = rest.strip_prefix('{') {
+                    comment = Some((index + 1, String::new()));
+                    rest = after;
+                    continue;
+                }
+
+                let token_end = rest.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(rest.len());
+                let token = &rest[..token_end];
+                rest = &rest[token_end..];
+
+                if token.is_empty() {
+                    continue;
+                }
+
+                if movetext_result.is_some() {
+                    return Err(GameRecordError::new(index + 1, format!("\"{token}\" after the result")));
+                }
+
+                if let Some(result) = GameResult::parse(token) {
+                    movetext_result = Some(result);
+                } else if token.strip_suffix('.').is_some_and(|number| number.parse::<usize>().is_ok()) {
+                    continue;
+                } else {
+                    match token.parse::<usize>() {
+                        Ok(col) if (1..=COLS).contains(&col) => record.moves.push(RecordedMove::new(col - 1)),
+                        _ => return Err(GameRecordError::new(index + 1, format!("unexpected \"{token}\""))),
+                    }
+                }
+            }
+        }
+
+        if let Some((start, _)) = comment {
+            return Err(GameRecordError::new(start, "unterminated comment"));
+        }
+
+        record.result = match (header_result, movetext_result) {
+            (Some(header_result), Some(movetext_result)) if header_result != movetext_result => {
+                return Err(GameRecordError::without_line("movetext result does not match the Result header"));
+            },
+            (header_result, movetext_result) => header_result.or(movetext_result).unwrap_or(GameResult::Unknown),
+        };
+
+        Ok(record)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+use crate::connect_four::state_array::StateArray;
+use crate::connect_four::state_bitboard::{StateBitboard, COL_BITS, IS_LEGAL};
+
+const THREAT_WEIGHT: i32 = 4;
+const PARITY_THREAT_WEIGHT: i32 = 3;
+const CENTER_WEIGHT: i32 = 1;
+
+// rows counted from 1 at the bottom; the first player wants threats on odd rows
+const ODD_ROWS: u64 = column_pattern(0b0010101);
+const EVEN_ROWS: u64 = column_pattern(0b0101010);
+const CENTER_COLUMN: u64 = 0b0111111 << (COLS / 2 * COL_BITS);
+
+const fn column_pattern(pattern: u64) -> u64 {
+    let mut mask = 0;
+    let mut col = 0;
+
+    while col < COLS {
+        mask |= pattern << (col * COL_BITS);
+        col += 1;
+    }
+
+    mask
+}
+
+pub trait StaticEvaluation: State {
+    // positive values favour the player to move
+    fn static_eval(&self) -> i32;
+}
+
+impl StaticEvaluation for StateBitboard {
+    fn static_eval(&self) -> i32 {
+        let empty = IS_LEGAL & !(self.curr_pieces | self.opp_pieces);
+        let curr_threats = winning_cells(self.curr_pieces) & empty;
+        let opp_threats = winning_cells(self.opp_pieces) & empty;
+
+        let (curr_parity, opp_parity) = if (self.moves_made & 1) == 0 {
+            (ODD_ROWS, EVEN_ROWS)
+        } else {
+            (EVEN_ROWS, ODD_ROWS)
+        };
+
+        THREAT_WEIGHT * (count(curr_threats) - count(opp_threats))
+            + PARITY_THREAT_WEIGHT * (count(curr_threats & curr_parity) - count(opp_threats & opp_parity))
+            + CENTER_WEIGHT * (count(self.curr_pieces & CENTER_COLUMN) - count(self.opp_pieces & CENTER_COLUMN))
+    }
+}
+
+impl StaticEvaluation for StateArray {
+    fn static_eval(&self) -> i32 {
+        let board: Vec<String> = self.decode().lines().map(String::from).collect();
+        StateBitboard::encode(&board).static_eval()
+    }
+}
+
+// cells that would complete four in a row for `pieces`, occupied or not
+pub fn winning_cells(pieces: u64) -> u64 {
+    let mut cells = (pieces << 1) & (pieces << 2) & (pieces << 3);
+
+    for shift in [COL_BITS - 1, COL_BITS, COL_BITS + 1] {
+        let pair = (pieces << shift) & (pieces << (2 * shift));
+        cells |= pair & (pieces << (3 * shift));
+        cells |= pair & (pieces >> shift);
+
+        let pair = (pieces >> shift) & (pieces >> (2 * shift));
+        cells |= pair & (pieces << shift);
+        cells |= pair & (pieces >> (3 * shift));
+    }
+
+    cells & IS_LEGAL
+}
+
+fn count(cells: u64) -> i32 {
+    cells.count_ones() as i32
+}
@@ -0,0 +1,241 @@
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::connect_four::solver::Solver;
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+
+const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+#[derive(Copy, Clone)]
+pub enum MctsBudget {
+    Iterations(usize),
+    Time(Duration),
+}
+
+pub struct MctsResult {
+    pub visits: [u32; COLS],
+    pub rewards: [f64; COLS],
+    pub iterations: usize,
+}
+
+impl MctsResult {
+    pub fn best_move(&self) -> Option<usize> {
+        (0..COLS)
+            .filter(|&col| self.visits[col] > 0)
+            .max_by_key(|&col| self.visits[col])
+    }
+
+    pub fn win_rate(&self, col: usize) -> Option<f64> {
+        (self.visits[col] > 0).then(|| self.rewards[col] / self.visits[col] as f64)
+    }
+}
+
+struct Node<S: State> {
+    state: S,
+    col: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried_moves: Vec<usize>,
+    visits: u32,
+    // from the perspective of the player who moved into this node
+    reward: f64,
+}
+
+impl<S: State> Node<S> {
+    fn new(state: S, col: usize, parent: Option<usize>) -> Self {
+        let untried_moves = if state.is_win() || state.board_full() {
+            vec![]
+        } else {
+            (0..COLS).filter(|&col| state.play_move(col).is_some()).collect()
+        };
+
+        Self {
+            state,
+            col,
+            parent,
+            children: vec![],
+            untried_moves,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+pub struct Mcts {
+    rng: Pcg64,
+    exploration: f64,
+}
+
+impl Mcts {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Pcg64::seed_from_u64(seed),
+            exploration: DEFAULT_EXPLORATION,
+        }
+    }
+
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn search<S: State>(&mut self, state: &S, budget: MctsBudget) -> MctsResult {
+        let start = Instant::now();
+        let mut tree = vec![Node::new(state.clone(), 0, None)];
+        let mut iterations = 0;
+
+        while !tree[0].untried_moves.is_empty() || !tree[0].children.is_empty() {
+            let done = match budget {
+                MctsBudget::Iterations(max_iterations) => iterations >= max_iterations,
+                MctsBudget::Time(time_limit) => start.elapsed() >= time_limit,
+            };
+
+            if done {
+                break;
+            }
+
+            let leaf = self.select_and_expand(&mut tree);
+            let reward = self.playout(&tree[leaf].state);
+            backpropagate(&mut tree, leaf, reward);
+            iterations += 1;
+        }
+
+        let mut result = MctsResult {
+            visits: [0; COLS],
+            rewards: [0.0; COLS],
+            iterations,
+        };
+
+        for &child in &tree[0].children {
+            result.visits[tree[child].col] = tree[child].visits;
+            result.rewards[tree[child].col] = tree[child].reward;
+        }
+
+        result
+    }
+
+    fn select_and_expand<S: State>(&mut self, tree: &mut Vec<Node<S>>) -> usize {
+        let mut node = 0;
+
+        loop {
+            if !tree[node].untried_moves.is_empty() {
+                let index = self.rng.random_range(0..tree[node].untried_moves.len());
+                let col = tree[node].untried_moves.swap_remove(index);
+                let next_state = tree[node].state.play_move(col).unwrap();
+
+                tree.push(Node::new(next_state, col, Some(node)));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+
+                return child;
+            }
+
+            if tree[node].children.is_empty() {
+                return node;
+            }
+
+            let log_visits = (tree[node].visits as f64).ln();
+
+            node = *tree[node]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    self.uct(&tree[a], log_visits).total_cmp(&self.uct(&tree[b], log_visits))
+                })
+                .unwrap();
+        }
+    }
+
+    fn uct<S: State>(&self, node: &Node<S>, parent_log_visits: f64) -> f64 {
+        let visits = node.visits as f64;
+        node.reward / visits + self.exploration * (parent_log_visits / visits).sqrt()
+    }
+
+    // reward for the player who moved into `state`: 1 for a win, 0.5 for a draw
+    fn playout<S: State>(&mut self, state: &S) -> f64 {
+        let mut state = state.clone();
+        let mut reward = 1.0;
+
+        loop {
+            if state.is_win() {
+                return reward;
+            }
+
+            if state.board_full() {
+                return 0.5;
+            }
+
+            let next_states = state.next_states();
+            let index = self.rng.random_range(0..next_states.len());
+            state = next_states.into_iter().nth(index).unwrap();
+            reward = 1.0 - reward;
+        }
+    }
+}
+
+fn backpropagate<S: State>(tree: &mut [Node<S>], leaf: usize, mut reward: f64) {
+    let mut node = Some(leaf);
+
+    while let Some(index) = node {
+        tree[index].visits += 1;
+        tree[index].reward += reward;
+        reward = 1.0 - reward;
+        node = tree[index].parent;
+    }
+}
+
+pub struct SolverAgreement {
+    pub positions: usize,
+    pub optimal_moves: usize,
+    pub total_score_loss: i32,
+}
+
+impl SolverAgreement {
+    pub fn optimal_rate(&self) -> f64 {
+        self.optimal_moves as f64 / self.positions as f64
+    }
+
+    pub fn average_score_loss(&self) -> f64 {
+        self.total_score_loss as f64 / self.positions as f64
+    }
+}
+
+// compares the move MCTS picks in each position with the exact solver's column scores.
+// positions the solver cannot score exactly within its budget are left out
+pub fn agreement_with_solver<S: State>(
+    states: &[S],
+    budget: MctsBudget,
+    seed: u64,
+    solver: &dyn Solver<S>,
+) -> SolverAgreement {
+
+    let mut mcts = Mcts::new(seed);
+    let mut agreement = SolverAgreement {
+        positions: 0,
+        optimal_moves: 0,
+        total_score_loss: 0,
+    };
+
+    for state in states {
+        let Some(col) = mcts.search(state, budget).best_move() else {
+            continue
+        };
+
+        let Some(scores) = solver.exact_column_scores(state) else {
+            continue
+        };
+
+        let best_score = scores.iter().flatten().max().unwrap();
+        let score_loss = best_score - scores[col].unwrap();
+
+        agreement.positions += 1;
+        agreement.total_score_loss += score_loss;
+
+        if score_loss == 0 {
+            agreement.optimal_moves += 1;
+        }
+    }
+
+    agreement
+}
@@ -0,0 +1,91 @@
+use crate::connect_four::solver_util::{COLS, EMPTY_CELL, FIRST_PLAYER, ROWS, SECOND_PLAYER};
+use crate::connect_four::state::State;
+
+// moves are written as 1-based column digits, e.g. "4453"
+pub fn parse_moves(moves: &str) -> Option<Vec<usize>> {
+    moves
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(col) if (1..=COLS as u32).contains(&col) => Some(col as usize - 1),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn format_moves(moves: &[usize]) -> String {
+    moves
+        .iter()
+        .map(|&col| char::from_digit(col as u32 + 1, 10).unwrap())
+        .collect()
+}
+
+// None if a move is illegal or is played after the game has ended
+pub fn play_moves<S: State>(moves: &[usize]) -> Option<S> {
+    let mut state = S::start_state();
+
+    for &col in moves {
+        if state.is_win() {
+            return None
+        }
+
+        state = state.play_move(col)?;
+    }
+
+    Some(state)
+}
+
+pub const GRID_ROW_SEPARATOR: char = '/';
+const GRID_EMPTY_CELL: char = '.';
+
+// a move string, or a grid of rows from the top down separated by '/' with '.' for empty cells,
+// e.g. "......./......./......./......./...O.../...X..."
+pub fn parse_position<S: State>(text: &str) -> Option<S> {
+    let text = text.trim();
+
+    if !text.contains(GRID_ROW_SEPARATOR) {
+        return play_moves(&parse_moves(text)?);
+    }
+
+    let rows: Vec<String> = text
+        .split(GRID_ROW_SEPARATOR)
+        .map(|row| row.replace(GRID_EMPTY_CELL, &EMPTY_CELL.to_string()))
+        .collect();
+
+    if rows.len() != ROWS || rows.iter().any(|row| row.chars().count() > COLS) {
+        return None
+    }
+
+    let cell = |row: usize, col: usize| rows[row].chars().nth(col).unwrap_or(EMPTY_CELL);
+    let mut first_player_pieces = 0;
+    let mut second_player_pieces = 0;
+
+    for col in 0..COLS {
+        let mut column_ended = false;
+
+        for row in (0..ROWS).rev() {
+            match cell(row, col) {
+                EMPTY_CELL => column_ended = true,
+                // a piece above an empty cell
+                _ if column_ended => return None,
+                FIRST_PLAYER => first_player_pieces += 1,
+                SECOND_PLAYER => second_player_pieces += 1,
+                _ => return None,
+            }
+        }
+    }
+
+    if first_player_pieces != second_player_pieces && first_player_pieces != second_player_pieces + 1 {
+        return None
+    }
+
+    Some(S::encode(&rows))
+}
+
+pub fn format_grid<S: State>(state: &S) -> String {
+    state
+        .decode()
+        .lines()
+        .map(|row| format!("{row:<COLS$}").replace(EMPTY_CELL, &GRID_EMPTY_CELL.to_string()))
+        .collect::<Vec<_>>()
+        .join(&GRID_ROW_SEPARATOR.to_string())
+}
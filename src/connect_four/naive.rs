@@ -1,5 +1,7 @@
 use std::cmp::{max};
-use crate::connect_four::solver_util::{EvaluatePositionReturn, DRAW, WORST_EVAL, BEST_EVAL};
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::solver_util::{nth_move, search_root, EvaluatePositionReturn, SearchContext, DRAW};
+use crate::connect_four::solver::SolverConfig;
 use crate::connect_four::state::State;
 
 
@@ -7,13 +9,22 @@ pub fn evaluate_position_rec<S: State>(
     state: S,
     mut alpha: i32,
     beta: i32,
-    positions_evaluated: &mut usize,
-) -> i32 {
+    ctx: &mut SearchContext,
+) -> Option<i32> {
+
+    if ctx.out_of_budget() {
+        return None
+    }
+
+    ctx.count_node(state.moves_made());
+    ctx.report_progress(|| None);
 
-    *positions_evaluated += 1;
-    
     if state.board_full() {
-        return DRAW;
+        return Some(DRAW);
+    }
+
+    if let Some(eval) = ctx.probe_tablebase(&state) {
+        return Some(eval);
     }
 
     let next_states = state.next_states();
@@ -21,32 +32,63 @@ pub fn evaluate_position_rec<S: State>(
     for next_state in &next_states {
 
         if next_state.is_win() {
-            return state.max_eval();
+            return Some(state.max_eval());
         }
     }
 
-    for next_state in next_states {
+    for (move_index, next_state) in next_states.into_iter().enumerate() {
+
+        ctx.trace_enter(|| nth_move(&state, move_index), (-beta, -alpha));
 
-        let eval = -evaluate_position_rec(
+        let eval = evaluate_position_rec(
             next_state,
             -beta,
             -alpha,
-            positions_evaluated,
+            ctx,
         );
 
+        ctx.trace_exit(eval);
+        let eval = -eval?;
+
         alpha = max(alpha, eval);
 
         if alpha >= beta {
-            return alpha;
+            ctx.record_cutoff(move_index);
+            return Some(alpha);
         }
     }
 
-    alpha
+    Some(alpha)
 }
 
 pub fn evaluate_position<S: State>(state: S) -> EvaluatePositionReturn {
-    let mut states_evaluated = 0;
-    let eval = evaluate_position_rec(state, WORST_EVAL, BEST_EVAL, &mut states_evaluated);
+    evaluate_position_with_budget(state, &SearchBudget::unlimited())
+}
+
+pub fn evaluate_position_with_budget<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()))
+}
+
+pub fn evaluate_position_with_stats<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()).with_stats(true))
+}
+
+pub fn evaluate_position_with_config<S: State>(state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+    let mut ctx = SearchContext::for_root(config, &state);
+    ctx.count_node(state.moves_made());
+
+    let (eval, bound) = search_root(&state, &ctx.finished_root_moves(), |col, next_state, alpha, beta| {
+        ctx.start_root_move(col, (-beta, -alpha));
+        ctx.trace_enter(|| col, (alpha, beta));
+        let eval = evaluate_position_rec(next_state, alpha, beta, &mut ctx);
+        ctx.trace_exit(eval);
+        ctx.finish_root_move(col, eval);
+        eval
+    });
 
-    EvaluatePositionReturn::new(eval, states_evaluated)
+    let mut ret = EvaluatePositionReturn::bounded(eval, ctx.states_evaluated, bound);
+    ret.stats = ctx.finish_stats();
+    ret.trace = ctx.finish_trace(eval);
+    ret.checkpoint_error = ctx.finish_checkpoint(bound, |_| Ok(()));
+    ret
 }
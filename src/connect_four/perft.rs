@@ -0,0 +1,98 @@
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+
+// number of move sequences of each length from the empty 6x7 board, where a
+// sequence stops once a player connects four (OEIS A212693)
+pub const PUBLISHED_PERFT: [usize; 11] = [
+    1,
+    7,
+    49,
+    343,
+    2401,
+    16807,
+    117649,
+    823536,
+    5673234,
+    39394572,
+    268031646,
+];
+
+pub struct PerftMismatch {
+    pub depth: usize,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+fn is_terminal<S: State>(state: &S) -> bool {
+    state.is_win() || state.board_full()
+}
+
+pub fn perft<S: State>(state: &S, depth: usize) -> usize {
+    if depth == 0 {
+        return 1
+    }
+
+    if is_terminal(state) {
+        return 0
+    }
+
+    if depth == 1 {
+        return state.next_states().len()
+    }
+
+    state
+        .next_states()
+        .iter()
+        .map(|next_state| perft(next_state, depth - 1))
+        .sum()
+}
+
+pub fn perft_divide<S: State>(state: &S, depth: usize) -> [Option<usize>; COLS] {
+    let mut counts = [None; COLS];
+
+    if depth == 0 || is_terminal(state) {
+        return counts
+    }
+
+    for (col, count) in counts.iter_mut().enumerate() {
+        *count = state.play_move(col).map(|next_state| perft(&next_state, depth - 1));
+    }
+
+    counts
+}
+
+// counts[d] is the number of leaf positions at depth d
+pub fn perft_by_depth<S: State>(state: &S, max_depth: usize) -> Vec<usize> {
+    let mut counts = vec![0; max_depth + 1];
+    count_by_depth(state, 0, &mut counts);
+    counts
+}
+
+fn count_by_depth<S: State>(state: &S, depth: usize, counts: &mut [usize]) {
+    counts[depth] += 1;
+
+    if depth + 1 == counts.len() || is_terminal(state) {
+        return
+    }
+
+    for next_state in state.next_states() {
+        count_by_depth(&next_state, depth + 1, counts);
+    }
+}
+
+pub fn verify_published<S: State>(max_depth: usize) -> Result<(), PerftMismatch> {
+    let max_depth = max_depth.min(PUBLISHED_PERFT.len() - 1);
+    let counts = perft_by_depth(&S::start_state(), max_depth);
+
+    for (depth, (&expected, &actual)) in PUBLISHED_PERFT.iter().zip(&counts).enumerate() {
+        if expected != actual {
+            return Err(PerftMismatch {
+                depth,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    Ok(())
+}
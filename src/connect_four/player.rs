@@ -0,0 +1,159 @@
+use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::depth_limited;
+use crate::connect_four::heuristic::StaticEvaluation;
+use crate::connect_four::mcts::{Mcts, MctsBudget};
+use crate::connect_four::solver::Solver;
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+
+pub trait Player<S: State>: Send {
+
+    fn name(&self) -> &str;
+
+    // None only when there is no legal move
+    fn choose_move(&mut self, state: &S) -> Option<usize>;
+}
+
+pub fn legal_moves<S: State>(state: &S) -> Vec<usize> {
+    (0..COLS).filter(|&col| state.play_move(col).is_some()).collect()
+}
+
+// plays perfectly whenever its solver finishes within the solver's budget
+pub struct SolverPlayer<S: State> {
+    name: String,
+    solver: Box<dyn Solver<S>>,
+    fallback: Option<Box<dyn Player<S>>>,
+}
+
+impl<S: State> SolverPlayer<S> {
+    pub fn new(solver: Box<dyn Solver<S>>) -> Self {
+        Self {
+            name: solver.name().to_string(),
+            solver,
+            fallback: None,
+        }
+    }
+
+    // used for positions the solver cannot finish within its budget, such as early openings
+    pub fn with_fallback(mut self, fallback: Box<dyn Player<S>>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    pub fn solver(&self) -> &dyn Solver<S> {
+        self.solver.as_ref()
+    }
+}
+
+impl<S: State> Player<S> for SolverPlayer<S> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_move(&mut self, state: &S) -> Option<usize> {
+        let scores = self.solver.column_scores(state);
+
+        match &mut self.fallback {
+            Some(fallback) if !scores.is_exact() => fallback.choose_move(state),
+            _ => scores.best_column(),
+        }
+    }
+}
+
+pub struct DepthLimitedPlayer {
+    name: String,
+    max_depth: usize,
+    time_limit: Option<Duration>,
+}
+
+impl DepthLimitedPlayer {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            name: format!("depth {max_depth}"),
+            max_depth,
+            time_limit: None,
+        }
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+}
+
+impl<S: StaticEvaluation> Player<S> for DepthLimitedPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_move(&mut self, state: &S) -> Option<usize> {
+        let budget = match self.time_limit {
+            Some(time_limit) => SearchBudget::unlimited().with_time_limit(time_limit),
+            None => SearchBudget::unlimited(),
+        };
+
+        depth_limited::search_with_budget(state, self.max_depth, &budget).best_move
+    }
+}
+
+pub struct RandomPlayer {
+    rng: Pcg64,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<S: State> Player<S> for RandomPlayer {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn choose_move(&mut self, state: &S) -> Option<usize> {
+        let moves = legal_moves(state);
+
+        if moves.is_empty() {
+            return None
+        }
+
+        Some(moves[self.rng.random_range(0..moves.len())])
+    }
+}
+
+pub struct MctsPlayer {
+    name: String,
+    mcts: Mcts,
+    budget: MctsBudget,
+}
+
+impl MctsPlayer {
+    pub fn new(seed: u64, budget: MctsBudget) -> Self {
+        let name = match budget {
+            MctsBudget::Iterations(iterations) => format!("mcts {iterations}"),
+            MctsBudget::Time(time_limit) => format!("mcts {}ms", time_limit.as_millis()),
+        };
+
+        Self {
+            name,
+            mcts: Mcts::new(seed),
+            budget,
+        }
+    }
+}
+
+impl<S: State> Player<S> for MctsPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_move(&mut self, state: &S) -> Option<usize> {
+        self.mcts.search(state, self.budget).best_move()
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// reading the clock every node is noticeably slower than the search itself
+const PROGRESS_CHECK_INTERVAL: usize = 1 << 12;
+
+#[derive(Clone, Debug)]
+pub struct SearchProgress {
+    pub nodes: usize,
+    pub elapsed: Duration,
+    pub nodes_per_second: f64,
+    // the root column being searched, and its window from the root's side
+    pub root_move: Option<usize>,
+    pub window: (i32, i32),
+    // None for solvers without a cache
+    pub cache_entries: Option<usize>,
+}
+
+pub trait ProgressObserver: Send + Sync {
+    fn on_progress(&self, progress: &SearchProgress);
+}
+
+impl<F: Fn(&SearchProgress) + Send + Sync> ProgressObserver for F {
+    fn on_progress(&self, progress: &SearchProgress) {
+        self(progress)
+    }
+}
+
+#[derive(Clone)]
+pub struct ProgressReporting {
+    pub observer: Arc<dyn ProgressObserver>,
+    pub interval: Duration,
+}
+
+pub struct ProgressTracker {
+    reporting: ProgressReporting,
+    started: Instant,
+    next_report: Instant,
+    root_move: Option<usize>,
+    window: (i32, i32),
+    cache_entries: Option<usize>,
+}
+
+impl ProgressTracker {
+    pub fn new(reporting: ProgressReporting) -> Self {
+        let started = Instant::now();
+
+        Self {
+            next_report: started + reporting.interval,
+            reporting,
+            started,
+            root_move: None,
+            window: (0, 0),
+            cache_entries: None,
+        }
+    }
+
+    pub fn start_root_move(&mut self, col: usize, window: (i32, i32)) {
+        self.root_move = Some(col);
+        self.window = window;
+    }
+
+    // `cache_entries` is only called when a report is due. solvers with a cache pass its size, and
+    // None keeps the size last passed, since the caching solvers hand their deepest plies to the plain search
+    pub fn maybe_report(&mut self, nodes: usize, cache_entries: impl FnOnce() -> Option<usize>) {
+        if !nodes.is_multiple_of(PROGRESS_CHECK_INTERVAL) {
+            return
+        }
+
+        let now = Instant::now();
+
+        if now < self.next_report {
+            return
+        }
+
+        self.next_report = now + self.reporting.interval;
+        self.cache_entries = cache_entries().or(self.cache_entries);
+
+        let elapsed = now - self.started;
+
+        self.reporting.observer.on_progress(&SearchProgress {
+            nodes,
+            elapsed,
+            nodes_per_second: nodes as f64 / elapsed.as_secs_f64().max(1e-9),
+            root_move: self.root_move,
+            window: self.window,
+            cache_entries: self.cache_entries,
+        });
+    }
+}
@@ -0,0 +1,145 @@
+use crate::connect_four::solver_util::Outcome;
+use crate::connect_four::state::State;
+
+pub const DEFAULT_MAX_NODES: usize = 2_000_000;
+const INFINITY: u32 = u32::MAX;
+
+pub struct ProofNumberResult {
+    pub outcome: Option<Outcome>,
+    pub nodes_expanded: usize,
+}
+
+// the player to move at the root either wants a win, or is happy with a draw
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ProofTarget {
+    Win,
+    AtLeastDraw,
+}
+
+struct Node<S: State> {
+    state: S,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    proof: u32,
+    disproof: u32,
+    root_player_to_move: bool,
+}
+
+impl<S: State> Node<S> {
+    fn new(state: S, parent: Option<usize>, root_player_to_move: bool, target: ProofTarget) -> Self {
+        let (proof, disproof) = if state.is_win() {
+            // whoever just moved has won
+            if root_player_to_move { (INFINITY, 0) } else { (0, INFINITY) }
+        } else if state.board_full() {
+            if target == ProofTarget::AtLeastDraw { (0, INFINITY) } else { (INFINITY, 0) }
+        } else {
+            (1, 1)
+        };
+
+        Self {
+            state,
+            parent,
+            children: vec![],
+            proof,
+            disproof,
+            root_player_to_move,
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.proof == 0 || self.disproof == 0
+    }
+}
+
+pub fn prove<S: State>(state: S, target: ProofTarget, max_nodes: usize) -> (Option<bool>, usize) {
+    let mut tree = vec![Node::new(state, None, true, target)];
+    let mut nodes_expanded = 0;
+
+    while !tree[0].is_solved() && tree.len() < max_nodes {
+        let most_proving = select_most_proving(&tree);
+        expand(&mut tree, most_proving, target);
+        nodes_expanded += 1;
+        update_ancestors(&mut tree, most_proving);
+    }
+
+    let proven = if tree[0].proof == 0 {
+        Some(true)
+    } else if tree[0].disproof == 0 {
+        Some(false)
+    } else {
+        None
+    };
+
+    (proven, nodes_expanded)
+}
+
+pub fn solve<S: State>(state: S, max_nodes: usize) -> ProofNumberResult {
+    let (win, mut nodes_expanded) = prove(state.clone(), ProofTarget::Win, max_nodes);
+
+    let outcome = match win {
+        Some(true) => Some(Outcome::Win),
+        Some(false) => {
+            let (at_least_draw, draw_nodes_expanded) = prove(state, ProofTarget::AtLeastDraw, max_nodes);
+            nodes_expanded += draw_nodes_expanded;
+
+            at_least_draw.map(|at_least_draw| if at_least_draw { Outcome::Draw } else { Outcome::Loss })
+        },
+        None => None,
+    };
+
+    ProofNumberResult {
+        outcome,
+        nodes_expanded,
+    }
+}
+
+fn select_most_proving<S: State>(tree: &[Node<S>]) -> usize {
+    let mut node = 0;
+
+    while !tree[node].children.is_empty() {
+        let children = tree[node].children.iter().copied();
+
+        node = if tree[node].root_player_to_move {
+            children.min_by_key(|&child| tree[child].proof).unwrap()
+        } else {
+            children.min_by_key(|&child| tree[child].disproof).unwrap()
+        };
+    }
+
+    node
+}
+
+fn expand<S: State>(tree: &mut Vec<Node<S>>, node: usize, target: ProofTarget) {
+    let root_player_to_move = !tree[node].root_player_to_move;
+
+    for next_state in tree[node].state.next_states() {
+        tree.push(Node::new(next_state, Some(node), root_player_to_move, target));
+        let child = tree.len() - 1;
+        tree[node].children.push(child);
+    }
+}
+
+fn update_ancestors<S: State>(tree: &mut [Node<S>], mut node: usize) {
+    loop {
+        let children = &tree[node].children;
+        let min_proof = children.iter().map(|&child| tree[child].proof).min().unwrap_or(INFINITY);
+        let min_disproof = children.iter().map(|&child| tree[child].disproof).min().unwrap_or(INFINITY);
+        let sum_proof = children.iter().fold(0, |sum: u32, &child| sum.saturating_add(tree[child].proof));
+        let sum_disproof = children.iter().fold(0, |sum: u32, &child| sum.saturating_add(tree[child].disproof));
+
+        let (proof, disproof) = if tree[node].root_player_to_move {
+            (min_proof, sum_disproof)
+        } else {
+            (sum_proof, min_disproof)
+        };
+
+        let unchanged = proof == tree[node].proof && disproof == tree[node].disproof;
+        tree[node].proof = proof;
+        tree[node].disproof = disproof;
+
+        match tree[node].parent {
+            Some(parent) if !unchanged => node = parent,
+            _ => break,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::connect_four::solver::Solver;
+use crate::connect_four::solver_util::{BOARD_SIZE, COLS, DRAW, ROWS};
+use crate::connect_four::state::State;
+use crate::connect_four::state_file::METADATA_PREFIX;
+
+const FULL_COLUMN: &str = "x";
+
+// random games that end early before a position is given up on
+const MAX_RANDOM_POSITION_ATTEMPTS: usize = 1000;
+
+// a position where exactly one column wins for the player to move
+pub struct Puzzle<S: State> {
+    pub state: S,
+    pub solution: usize,
+    // counts the winner's moves, including the winning one
+    pub win_length: usize,
+    pub column_scores: [Option<i32>; COLS],
+}
+
+impl<S: State> Puzzle<S> {
+    pub fn from_scores(state: S, column_scores: [Option<i32>; COLS]) -> Option<Self> {
+        let mut winning_cols = (0..COLS).filter(|&col| column_scores[col].is_some_and(|score| score > DRAW));
+        let solution = winning_cols.next()?;
+
+        if winning_cols.next().is_some() {
+            return None
+        }
+
+        let win_length = win_length(&state, column_scores[solution].unwrap());
+
+        Some(Self {
+            state,
+            solution,
+            win_length,
+            column_scores,
+        })
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let scores: Vec<String> = self.column_scores
+            .iter()
+            .map(|score| score.map_or(FULL_COLUMN.to_string(), |score| score.to_string()))
+            .collect();
+
+        writeln!(writer, "{METADATA_PREFIX} solution: {}", self.solution + 1)?;
+        writeln!(writer, "{METADATA_PREFIX} win_length: {}", self.win_length)?;
+        writeln!(writer, "{METADATA_PREFIX} scores: {}", scores.join(" "))?;
+        write!(writer, "{}", self.state.decode())
+    }
+}
+
+// the number of moves the player to move needs to win when `state` is worth `score`
+pub fn win_length<S: State>(state: &S, score: i32) -> usize {
+    (state.max_eval() - score + 1) as usize
+}
+
+pub fn find_puzzle<S: State>(state: S, solver: &dyn Solver<S>, min_win_length: usize) -> Option<Puzzle<S>> {
+    if state.is_win() || state.board_full() {
+        return None
+    }
+
+    // a bound could make a losing column look like a win
+    let scores = solver.exact_column_scores(&state)?;
+
+    Puzzle::from_scores(state, scores).filter(|puzzle| puzzle.win_length >= min_win_length)
+}
+
+// stops after `max_puzzles` puzzles; candidates seen before are skipped
+pub fn search_puzzles<S: State>(
+    candidates: impl IntoIterator<Item = S>,
+    solver: &dyn Solver<S>,
+    min_win_length: usize,
+    max_puzzles: usize,
+) -> Vec<Puzzle<S>> {
+
+    let mut seen = HashSet::new();
+    let mut puzzles = vec![];
+
+    for state in candidates {
+        if puzzles.len() >= max_puzzles {
+            break;
+        }
+
+        if !seen.insert(state.key()) {
+            continue;
+        }
+
+        if let Some(puzzle) = find_puzzle(state, solver, min_win_length) {
+            puzzles.push(puzzle);
+        }
+    }
+
+    puzzles
+}
+
+// plays random moves that do not end the game; None if the game could not be kept going
+pub fn random_position<S: State>(rng: &mut Pcg64, moves_made: usize) -> Option<S> {
+    let mut state = S::start_state();
+
+    while state.moves_made() < moves_made {
+        let next_states: Vec<S> = state
+            .next_states()
+            .into_iter()
+            .filter(|next_state| !next_state.is_win() && !next_state.board_full())
+            .collect();
+
+        if next_states.is_empty() {
+            return None
+        }
+
+        let index = rng.random_range(0..next_states.len());
+        state = next_states.into_iter().nth(index).unwrap();
+    }
+
+    Some(state)
+}
+
+// positions this early take the solvers a long time, so keep `moves_made` in the high teens or later.
+// fewer than `count` positions come out when random games keep ending before `moves_made`,
+// and none when a game cannot still be going after that many moves
+pub fn random_positions<S: State>(seed: u64, count: usize, moves_made: usize) -> impl Iterator<Item = S> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+
+    std::iter::from_fn(move || {
+        if moves_made >= BOARD_SIZE {
+            return None
+        }
+
+        (0..MAX_RANDOM_POSITION_ATTEMPTS).find_map(|_| random_position(&mut rng, moves_made))
+    })
+    .take(count)
+}
+
+pub fn write_puzzles<S: State, W: Write>(writer: &mut W, puzzles: &[Puzzle<S>]) -> io::Result<()> {
+    for puzzle in puzzles {
+        puzzle.write_to(writer)?;
+    }
+
+    Ok(())
+}
+
+pub fn write_puzzle_file<S: State, P: AsRef<Path>>(path: P, puzzles: &[Puzzle<S>]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_puzzles(&mut writer, puzzles)?;
+    writer.flush()
+}
+
+pub fn read_puzzle_file<S: State, P: AsRef<Path>>(path: P) -> io::Result<Vec<Puzzle<S>>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut puzzles = vec![];
+    let mut metadata = vec![];
+    let mut curr_state = vec![];
+
+    for line_result in reader.lines() {
+        let line = line_result?;
+
+        if let Some(entry) = line.strip_prefix(METADATA_PREFIX) {
+            if let Some((name, value)) = entry.split_once(':') {
+                metadata.push((name.trim().to_string(), value.trim().to_string()));
+            }
+
+            continue;
+        }
+
+        curr_state.push(line);
+
+        if curr_state.len() == ROWS {
+            let state = S::encode(&curr_state);
+            let scores = parse_scores(&metadata).ok_or_else(|| invalid_data("puzzle has no valid scores"))?;
+
+            let puzzle = Puzzle::from_scores(state, scores)
+                .ok_or_else(|| invalid_data("puzzle does not have exactly one winning column"))?;
+
+            if let Some(solution) = metadata_value(&metadata, "solution")
+                && solution.parse::<usize>().ok() != Some(puzzle.solution + 1)
+            {
+                return Err(invalid_data("puzzle solution does not match its scores"));
+            }
+
+            puzzles.push(puzzle);
+            metadata.clear();
+            curr_state.clear();
+        }
+    }
+
+    Ok(puzzles)
+}
+
+fn metadata_value<'a>(metadata: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    metadata.iter().rev().find(|(entry_name, _)| entry_name == name).map(|(_, value)| value.as_str())
+}
+
+fn parse_scores(metadata: &[(String, String)]) -> Option<[Option<i32>; COLS]> {
+    let mut scores = [None; COLS];
+    let mut values = metadata_value(metadata, "scores")?.split_whitespace();
+
+    for score in &mut scores {
+        let value = values.next()?;
+        *score = if value == FULL_COLUMN { None } else { Some(value.parse().ok()?) };
+    }
+
+    values.next().is_none().then_some(scores)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,128 @@
+use std::cmp::max;
+use std::fmt;
+use std::time::Duration;
+use serde::{Serialize, Serializer};
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SearchStats {
+    pub root_ply: usize,
+    pub nodes: usize,
+    pub cache_probes: usize,
+    pub cache_hits: usize,
+    pub cutoffs: usize,
+    pub first_move_cutoffs: usize,
+    pub nodes_per_depth: Vec<usize>,
+    pub peak_alpha_cache_size: usize,
+    pub peak_beta_cache_size: usize,
+    #[serde(rename = "wall_time_secs", serialize_with = "serialize_secs")]
+    pub wall_time: Duration,
+}
+
+impl SearchStats {
+    pub fn new(root_ply: usize) -> Self {
+        Self {
+            root_ply,
+            ..Self::default()
+        }
+    }
+
+    pub fn record_node(&mut self, ply: usize) {
+        let depth = ply.saturating_sub(self.root_ply);
+
+        if depth >= self.nodes_per_depth.len() {
+            self.nodes_per_depth.resize(depth + 1, 0);
+        }
+
+        self.nodes += 1;
+        self.nodes_per_depth[depth] += 1;
+    }
+
+    pub fn record_cache_probe(&mut self, hit: bool) {
+        self.cache_probes += 1;
+
+        if hit {
+            self.cache_hits += 1;
+        }
+    }
+
+    pub fn record_cutoff(&mut self, move_index: usize) {
+        self.cutoffs += 1;
+
+        if move_index == 0 {
+            self.first_move_cutoffs += 1;
+        }
+    }
+
+    pub fn record_cache_sizes(&mut self, alpha_cache_size: usize, beta_cache_size: usize) {
+        self.peak_alpha_cache_size = max(self.peak_alpha_cache_size, alpha_cache_size);
+        self.peak_beta_cache_size = max(self.peak_beta_cache_size, beta_cache_size);
+    }
+
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.cache_probes += other.cache_probes;
+        self.cache_hits += other.cache_hits;
+        self.cutoffs += other.cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+
+        if other.nodes_per_depth.len() > self.nodes_per_depth.len() {
+            self.nodes_per_depth.resize(other.nodes_per_depth.len(), 0);
+        }
+
+        for (depth, nodes) in other.nodes_per_depth.iter().enumerate() {
+            self.nodes_per_depth[depth] += nodes;
+        }
+
+        self.record_cache_sizes(other.peak_alpha_cache_size, other.peak_beta_cache_size);
+        self.wall_time = max(self.wall_time, other.wall_time);
+    }
+
+    pub fn cache_hit_rate(&self) -> f64 {
+        ratio(self.cache_hits, self.cache_probes)
+    }
+
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        ratio(self.first_move_cutoffs, self.cutoffs)
+    }
+
+    pub fn nodes_per_second(&self) -> f64 {
+        self.nodes as f64 / self.wall_time.as_secs_f64()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("search stats are always serializable")
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+fn serialize_secs<Ser: Serializer>(duration: &Duration, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes:              {}", self.nodes)?;
+        writeln!(f, "wall time:          {:.3?}", self.wall_time)?;
+        writeln!(f, "nodes per second:   {:.0}", self.nodes_per_second())?;
+        writeln!(f, "cache probes:       {}", self.cache_probes)?;
+        writeln!(f, "cache hits:         {} ({:.1}%)", self.cache_hits, 100.0 * self.cache_hit_rate())?;
+        writeln!(f, "cutoffs:            {}", self.cutoffs)?;
+        writeln!(f, "first move cutoffs: {} ({:.1}%)", self.first_move_cutoffs, 100.0 * self.first_move_cutoff_rate())?;
+        writeln!(f, "peak alpha cache:   {}", self.peak_alpha_cache_size)?;
+        writeln!(f, "peak beta cache:    {}", self.peak_beta_cache_size)?;
+        writeln!(f, "nodes per depth:")?;
+
+        for (depth, nodes) in self.nodes_per_depth.iter().enumerate() {
+            writeln!(f, "  {depth:>2} (ply {:>2}): {nodes}", self.root_ply + depth)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde_json::Value;
+use crate::connect_four::budget::CancelToken;
+use crate::connect_four::service::{parse_request, AnalysisService, Request, Response};
+use crate::connect_four::state::State;
+
+pub const DEFAULT_MAX_CONCURRENT_SOLVES: usize = 4;
+
+// bounds how many requests are solved at once across every client
+struct SolveLimiter {
+    max_active: usize,
+    active: Mutex<usize>,
+}
+
+struct SolvePermit<'a> {
+    limiter: &'a SolveLimiter,
+}
+
+impl SolveLimiter {
+    fn new(max_active: usize) -> Self {
+        Self {
+            max_active: max_active.max(1),
+            active: Mutex::new(0),
+        }
+    }
+
+    // None when `max_active` requests are already being solved
+    fn try_acquire(&self) -> Option<SolvePermit<'_>> {
+        let mut active = self.active.lock().unwrap();
+
+        if *active >= self.max_active {
+            return None
+        }
+
+        *active += 1;
+        Some(SolvePermit { limiter: self })
+    }
+}
+
+impl Drop for SolvePermit<'_> {
+    fn drop(&mut self) {
+        *self.limiter.active.lock().unwrap() -= 1;
+    }
+}
+
+// serves the JSON-lines protocol of `AnalysisService` to many clients at once, sharing its caches.
+// requests on one connection are solved concurrently and answered as they finish, a request that
+// arrives while the most requests are already being solved is rejected with an error, and
+// `{"cancel": <id>}` stops that connection's request with the given id
+pub struct Server<S: State> {
+    service: AnalysisService<S>,
+    limiter: SolveLimiter,
+}
+
+impl<S: State> Server<S> {
+    pub fn new(max_concurrent_solves: usize) -> Arc<Self> {
+        Arc::new(Self {
+            service: AnalysisService::new(),
+            limiter: SolveLimiter::new(max_concurrent_solves),
+        })
+    }
+
+    pub fn service(&self) -> &AnalysisService<S> {
+        &self.service
+    }
+
+    // runs for as long as the listener does, with a thread per client. a connection that fails
+    // to be accepted is logged and skipped
+    pub fn serve_tcp(self: &Arc<Self>, listener: TcpListener) {
+        self.serve_incoming(listener.incoming(), TcpStream::try_clone);
+    }
+
+    #[cfg(unix)]
+    pub fn serve_unix(self: &Arc<Self>, listener: UnixListener) {
+        self.serve_incoming(listener.incoming(), UnixStream::try_clone);
+    }
+
+    fn serve_incoming<T: Read + Write + Send + 'static>(
+        self: &Arc<Self>,
+        incoming: impl Iterator<Item = io::Result<T>>,
+        try_clone: fn(&T) -> io::Result<T>,
+    ) {
+        for stream in incoming {
+            let (reader, stream) = match stream.and_then(|stream| Ok((try_clone(&stream)?, stream))) {
+                Ok(streams) => streams,
+                Err(err) => {
+                    eprintln!("failed to accept a connection: {err}");
+                    continue;
+                },
+            };
+
+            let server = self.clone();
+            thread::spawn(move || server.serve_connection(BufReader::new(reader), stream));
+        }
+    }
+
+    pub fn serve_connection<R: BufRead, W: Write + Send>(&self, mut reader: R, writer: W) -> io::Result<()> {
+        let writer = Mutex::new(writer);
+        let running: Mutex<HashMap<String, CancelToken>> = Mutex::new(HashMap::new());
+        let mut line = vec![];
+
+        thread::scope(|scope| {
+            loop {
+                line.clear();
+
+                if reader.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+
+                let line = String::from_utf8_lossy(&line);
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                if let Some(id) = cancel_target(&line) {
+                    match running.lock().unwrap().get(&id.to_string()) {
+                        Some(cancel_token) => cancel_token.cancel(),
+                        None => send(&writer, &Response::error(id, "no running request with this id"))?,
+                    }
+
+                    continue;
+                }
+
+                let request = match parse_request(&line) {
+                    Ok(request) => request,
+                    Err(malformed) => {
+                        send(&writer, &malformed.into_response())?;
+                        continue;
+                    },
+                };
+
+                let key = request.id.to_string();
+                let cancel_token = CancelToken::new();
+
+                let permit = {
+                    let mut running = running.lock().unwrap();
+
+                    if !request.id.is_null() && running.contains_key(&key) {
+                        Err("a request with this id is already running".to_string())
+                    } else if let Some(permit) = self.limiter.try_acquire() {
+                        // requests without an id cannot be cancelled, so they need no entry
+                        if !request.id.is_null() {
+                            running.insert(key.clone(), cancel_token.clone());
+                        }
+
+                        Ok(permit)
+                    } else {
+                        Err(format!("the server is already solving {} requests", self.limiter.max_active))
+                    }
+                };
+
+                let permit = match permit {
+                    Ok(permit) => permit,
+                    Err(message) => {
+                        send(&writer, &Response::error(request.id, message))?;
+                        continue;
+                    },
+                };
+
+                let (writer, running) = (&writer, &running);
+                scope.spawn(move || self.solve(&request, &key, &cancel_token, permit, running, writer));
+            }
+
+            // a client that closes its side after sending still gets its answers
+            Ok(())
+        })
+    }
+
+    fn solve<W: Write>(
+        &self,
+        request: &Request,
+        key: &str,
+        cancel_token: &CancelToken,
+        permit: SolvePermit<'_>,
+        running: &Mutex<HashMap<String, CancelToken>>,
+        writer: &Mutex<W>,
+    ) {
+        let response = self.service.handle(request, Some(cancel_token));
+        drop(permit);
+        running.lock().unwrap().remove(key);
+
+        // the client may have gone away
+        send(writer, &response).ok();
+    }
+}
+
+fn send<W: Write>(writer: &Mutex<W>, response: &Response) -> io::Result<()> {
+    let mut writer = writer.lock().unwrap();
+    writeln!(writer, "{}", response.to_json())?;
+    writer.flush()
+}
+
+// the id in `{"cancel": <id>}`
+fn cancel_target(line: &str) -> Option<Value> {
+    match serde_json::from_str::<Value>(line).ok()? {
+        Value::Object(mut fields) if fields.len() == 1 => fields.remove("cancel"),
+        _ => None,
+    }
+}
@@ -0,0 +1,222 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::connect_four::budget::{CancelToken, SearchBudget};
+use crate::connect_four::move_string::parse_position;
+use crate::connect_four::solver::{SharedCachingSession, SolverConfig, SolverRegistry, ThreadsSession};
+use crate::connect_four::solver_util::COLS;
+use crate::connect_four::state::State;
+use crate::connect_four::threads::SharedStateCache;
+
+pub const DEFAULT_SOLVER: &str = "caching";
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisType {
+    // the score of the position only
+    #[default]
+    Eval,
+    // the score of every column, and the best move
+    Columns,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Request {
+    // echoed back in the response
+    #[serde(default)]
+    pub id: Value,
+    // a move string or a grid, see `move_string::parse_position`
+    #[serde(default)]
+    pub position: String,
+    pub solver: Option<String>,
+    pub time_ms: Option<u64>,
+    pub max_nodes: Option<usize>,
+    #[serde(default)]
+    pub analysis: AnalysisType,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Response {
+    pub id: Value,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eval: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exact: Option<bool>,
+    // 1-based like move strings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_move: Option<usize>,
+    // null for full columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<[Option<i32>; COLS]>,
+    pub nodes: usize,
+    pub time_ms: f64,
+    // the search was cancelled, so the result is only a bound
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+}
+
+impl Response {
+    pub fn error(id: Value, message: impl Into<String>) -> Self {
+        Self {
+            id,
+            ok: false,
+            error: Some(message.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("responses are always serializable")
+    }
+}
+
+// answers analysis requests with a cache that persists between requests. the caching and threads
+// searches share it, and any number of requests can search it at once
+pub struct AnalysisService<S: State> {
+    solvers: SolverRegistry<S>,
+    cache: Arc<SharedStateCache<S>>,
+}
+
+impl<S: State> AnalysisService<S> {
+    pub fn new() -> Self {
+        let cache = Arc::new(SharedStateCache::new());
+        let mut solvers = SolverRegistry::with_default_solvers();
+
+        let caching_cache = cache.clone();
+        solvers.register("caching", move |config| Box::new(SharedCachingSession::with_shared_cache(config, caching_cache.clone())));
+
+        let threads_cache = cache.clone();
+        solvers.register("threads", move |config| Box::new(ThreadsSession::with_shared_cache(config, threads_cache.clone())));
+
+        Self { solvers, cache }
+    }
+
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn handle_line(&self, line: &str) -> Response {
+        match parse_request(line) {
+            Ok(request) => self.handle(&request, None),
+            Err(malformed) => malformed.into_response(),
+        }
+    }
+
+    pub fn handle(&self, request: &Request, cancel_token: Option<&CancelToken>) -> Response {
+        let start = Instant::now();
+        let solver_name = request.solver.as_deref().unwrap_or(DEFAULT_SOLVER);
+
+        // the budget covers the whole request, however many searches it takes
+        let mut budget = SearchBudget::unlimited();
+
+        if let Some(time_ms) = request.time_ms {
+            budget = budget.with_time_limit(Duration::from_millis(time_ms));
+        }
+
+        if let Some(max_nodes) = request.max_nodes {
+            budget = budget.with_max_nodes(max_nodes);
+        }
+
+        if let Some(cancel_token) = cancel_token {
+            budget = budget.with_cancel_token(cancel_token.clone());
+        }
+
+        let Some(solver) = self.solvers.create(solver_name, SolverConfig::default().with_budget(budget)) else {
+            return Response::error(request.id.clone(), format!("unknown solver \"{solver_name}\""));
+        };
+
+        let Some(state) = parse_position::<S>(&request.position) else {
+            return Response::error(request.id.clone(), format!("invalid position \"{}\"", request.position));
+        };
+
+        if state.is_win() || state.board_full() {
+            return Response::error(request.id.clone(), "the game is already over");
+        }
+
+        let mut response = Response {
+            id: request.id.clone(),
+            ok: true,
+            ..Response::default()
+        };
+
+        match request.analysis {
+            AnalysisType::Eval => {
+                let ret = solver.evaluate(state);
+                response.eval = Some(ret.eval);
+                response.exact = Some(ret.is_exact());
+                response.nodes = ret.states_evaluated;
+            },
+            AnalysisType::Columns => {
+                let scores = solver.column_scores(&state);
+                response.eval = scores.best_score();
+                response.exact = Some(scores.is_exact());
+                response.best_move = scores.best_column().map(|col| col + 1);
+                response.columns = Some(scores.scores);
+                response.nodes = scores.states_evaluated;
+            },
+        }
+
+        response.cancelled = response.exact == Some(false) && cancel_token.is_some_and(CancelToken::is_cancelled);
+        response.time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        response
+    }
+
+    // one request per line in, one response per line out, until the end of the input;
+    // malformed lines get an error response instead of ending the loop
+    pub fn serve_lines<R: BufRead, W: Write>(&self, mut input: R, mut output: W) -> io::Result<()> {
+        let mut line = vec![];
+
+        loop {
+            line.clear();
+
+            if input.read_until(b'\n', &mut line)? == 0 {
+                return Ok(());
+            }
+
+            // invalid UTF-8 is left for the JSON parser to reject
+            let line = String::from_utf8_lossy(&line);
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            writeln!(output, "{}", self.handle_line(&line).to_json())?;
+            output.flush()?;
+        }
+    }
+}
+
+pub struct MalformedRequest {
+    pub id: Value,
+    pub message: String,
+}
+
+impl MalformedRequest {
+    pub fn into_response(self) -> Response {
+        Response::error(self.id, format!("malformed request: {}", self.message))
+    }
+}
+
+pub fn parse_request(line: &str) -> Result<Request, MalformedRequest> {
+    serde_json::from_str::<Request>(line).map_err(|err| MalformedRequest {
+        // a well-formed object with a bad field still gets its id back
+        id: serde_json::from_str::<Value>(line)
+            .ok()
+            .and_then(|value| value.get("id").cloned())
+            .unwrap_or(Value::Null),
+        message: err.to_string(),
+    })
+}
+
+impl<S: State> Default for AnalysisService<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
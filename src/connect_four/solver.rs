@@ -0,0 +1,459 @@
+use std::array;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::cache_strategy::StateCache;
+use crate::connect_four::checkpoint::CheckpointConfig;
+use crate::connect_four::progress::{ProgressObserver, ProgressReporting};
+use crate::connect_four::solver_util::{EvaluatePositionReturn, COLS, DEFAULT_MOVE_ORDER};
+use crate::connect_four::state::State;
+use crate::connect_four::tablebase::Tablebase;
+use crate::connect_four::threads::SharedStateCache;
+use crate::connect_four::trace::TraceConfig;
+use crate::connect_four::{cache_strategy, naive, threads};
+
+#[derive(Clone, Default)]
+pub struct SolverConfig {
+    pub budget: SearchBudget,
+    pub collect_stats: bool,
+    pub tablebase: Option<Arc<Tablebase>>,
+    pub progress: Option<ProgressReporting>,
+    pub trace: Option<TraceConfig>,
+    pub checkpoint: Option<CheckpointConfig>,
+}
+
+impl SolverConfig {
+    pub fn with_budget(mut self, budget: SearchBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn with_stats(mut self, collect_stats: bool) -> Self {
+        self.collect_stats = collect_stats;
+        self
+    }
+
+    pub fn with_tablebase(mut self, tablebase: Arc<Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    // the observer is called from the searching thread, at most once per interval
+    pub fn with_progress(mut self, observer: Arc<dyn ProgressObserver>, interval: Duration) -> Self {
+        self.progress = Some(ProgressReporting { observer, interval });
+        self
+    }
+
+    // only the naive and caching searches record a tree, returned in `EvaluatePositionReturn::trace`
+    pub fn with_trace(mut self, trace: TraceConfig) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    // writes the root-level state of each search to the checkpoint, and skips the root moves
+    // the checkpoint being resumed has finished. meant for solving one position at a time
+    pub fn with_checkpoint(mut self, checkpoint: CheckpointConfig) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+}
+
+pub trait Solver<S: State>: Send + Sync {
+
+    fn name(&self) -> &str;
+
+    fn config(&self) -> &SolverConfig;
+
+    // searches with another budget or tablebase than the solver's own
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn;
+
+    // whether positions gain from being solved on several threads at once. false for solvers that
+    // already search on threads of their own or that solve one position at a time
+    fn parallel_safe(&self) -> bool {
+        true
+    }
+
+    fn evaluate(&self, state: S) -> EvaluatePositionReturn {
+        self.evaluate_with_config(state, self.config())
+    }
+
+    fn column_scores(&self, state: &S) -> ColumnScores {
+        self.column_scores_with_config(state, self.config())
+    }
+
+    // the config's budget covers the searches of all the columns together
+    fn column_scores_with_config(&self, state: &S, config: &SolverConfig) -> ColumnScores {
+        let mut column_scores = ColumnScores {
+            scores: [None; COLS],
+            exact: [true; COLS],
+            states_evaluated: 0,
+        };
+
+        for column in scored_columns(self, state, config) {
+            column_scores.scores[column.col] = Some(column.score);
+            column_scores.exact[column.col] = column.exact;
+            column_scores.states_evaluated += column.states_evaluated;
+        }
+
+        column_scores
+    }
+
+    // None when a search ran out of budget
+    fn exact_column_scores(&self, state: &S) -> Option<[Option<i32>; COLS]> {
+        self.column_scores(state).exact_scores()
+    }
+
+    fn best_move(&self, state: &S) -> Option<usize> {
+        self.column_scores(state).best_column()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ScoredColumn {
+    pub col: usize,
+    // from the perspective of the player to move in the scored position
+    pub score: i32,
+    // false when the search ran out of budget, in which case the score is only an upper bound
+    pub exact: bool,
+    pub states_evaluated: usize,
+}
+
+// plays each column in move order and searches the position after it, one column at a time as
+// the iterator is advanced. the config's budget covers the searches of all the columns together
+pub fn scored_columns<'a, S: State, V: Solver<S> + ?Sized>(
+    solver: &'a V,
+    state: &'a S,
+    config: &'a SolverConfig,
+) -> impl Iterator<Item = ScoredColumn> + 'a {
+
+    let mut budget = config.budget.start_shared();
+
+    DEFAULT_MOVE_ORDER.into_iter().filter_map(move |col| {
+        let next_state = state.play_move(col)?;
+
+        if next_state.is_win() {
+            return Some(ScoredColumn { col, score: state.max_eval(), exact: true, states_evaluated: 0 })
+        }
+
+        let remaining = budget.remaining();
+
+        // even a search with no nodes left counts its root, which would take the columns over the limit
+        if remaining.max_nodes == Some(0) {
+            return Some(ScoredColumn { col, score: state.max_eval(), exact: false, states_evaluated: 0 })
+        }
+
+        let ret = solver.evaluate_with_config(next_state, &config.clone().with_budget(remaining));
+        budget.spend(ret.states_evaluated);
+
+        Some(ScoredColumn {
+            col,
+            score: -ret.eval,
+            exact: ret.is_exact(),
+            states_evaluated: ret.states_evaluated,
+        })
+    })
+}
+
+// the score of every column, from the perspective of the player to move in the scored position
+#[derive(Clone, Debug)]
+pub struct ColumnScores {
+    // None for full columns
+    pub scores: [Option<i32>; COLS],
+    // false for a column whose search ran out of budget, whose score is then only an upper bound
+    pub exact: [bool; COLS],
+    pub states_evaluated: usize,
+}
+
+impl ColumnScores {
+    pub fn is_exact(&self) -> bool {
+        self.exact.iter().all(|&exact| exact)
+    }
+
+    // the score of the position itself when every column is exact
+    pub fn best_score(&self) -> Option<i32> {
+        self.scores.iter().flatten().copied().max()
+    }
+
+    pub fn exact_scores(&self) -> Option<[Option<i32>; COLS]> {
+        self.is_exact().then_some(self.scores)
+    }
+
+    // an upper bound says little about a column, so those only count when no column is exact
+    pub fn best_column(&self) -> Option<usize> {
+        let exact_scores = array::from_fn(|col| self.scores[col].filter(|_| self.exact[col]));
+
+        best_column(&exact_scores).or_else(|| best_column(&self.scores))
+    }
+}
+
+pub fn best_column(scores: &[Option<i32>; COLS]) -> Option<usize> {
+    let mut best: Option<(usize, i32)> = None;
+
+    for col in DEFAULT_MOVE_ORDER {
+        if let Some(score) = scores[col]
+            && best.is_none_or(|(_, best_score)| score > best_score)
+        {
+            best = Some((col, score));
+        }
+    }
+
+    best.map(|(col, _)| col)
+}
+
+pub struct NaiveSolver {
+    config: SolverConfig,
+}
+
+impl NaiveSolver {
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S: State> Solver<S> for NaiveSolver {
+    fn name(&self) -> &str {
+        "naive"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        naive::evaluate_position_with_config(state, config)
+    }
+}
+
+pub struct CachingSolver {
+    config: SolverConfig,
+}
+
+impl CachingSolver {
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S: State> Solver<S> for CachingSolver {
+    fn name(&self) -> &str {
+        "caching"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        cache_strategy::evaluate_position_with_config(state, config)
+    }
+}
+
+pub struct ThreadsSolver {
+    config: SolverConfig,
+}
+
+impl ThreadsSolver {
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S: State> Solver<S> for ThreadsSolver {
+    fn name(&self) -> &str {
+        "threads"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        threads::evaluate_position_with_config(state, config)
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+}
+
+// keeps its cache between calls, for solving many related positions such as the plies of one game
+pub struct CachingSession<S: State> {
+    config: SolverConfig,
+    cache: Mutex<StateCache<S>>,
+}
+
+impl<S: State> CachingSession<S> {
+    pub fn new(config: SolverConfig) -> Self {
+        Self::with_cache(config, StateCache::new())
+    }
+
+    // starts from a cache such as one loaded with `StateCache::load`
+    pub fn with_cache(config: SolverConfig, cache: StateCache<S>) -> Self {
+        Self {
+            config,
+            cache: Mutex::new(cache),
+        }
+    }
+
+    pub fn cache_len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    pub fn save_cache<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.cache.lock().unwrap().save(path)
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+impl<S: State> Solver<S> for CachingSession<S> {
+    fn name(&self) -> &str {
+        "caching"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    // keeps this session's cache whatever the budget or tablebase
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        cache_strategy::evaluate_position_with_cache(state, &mut self.cache.lock().unwrap(), config)
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+}
+
+pub struct ThreadsSession<S: State> {
+    config: SolverConfig,
+    cache: Arc<SharedStateCache<S>>,
+}
+
+impl<S: State> ThreadsSession<S> {
+    pub fn new(config: SolverConfig) -> Self {
+        Self::with_shared_cache(config, Arc::new(SharedStateCache::new()))
+    }
+
+    pub fn with_shared_cache(config: SolverConfig, cache: Arc<SharedStateCache<S>>) -> Self {
+        Self { config, cache }
+    }
+
+    pub fn cache(&self) -> &Arc<SharedStateCache<S>> {
+        &self.cache
+    }
+}
+
+impl<S: State> Solver<S> for ThreadsSession<S> {
+    fn name(&self) -> &str {
+        "threads"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        threads::evaluate_position_with_cache(state, self.cache.clone(), config)
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+}
+
+// the caching search over a cache that any number of searches can use at once, for serving
+// requests concurrently. shares its cache type with `ThreadsSession`, so the two can share one cache
+pub struct SharedCachingSession<S: State> {
+    config: SolverConfig,
+    cache: Arc<SharedStateCache<S>>,
+}
+
+impl<S: State> SharedCachingSession<S> {
+    pub fn new(config: SolverConfig) -> Self {
+        Self::with_shared_cache(config, Arc::new(SharedStateCache::new()))
+    }
+
+    pub fn with_shared_cache(config: SolverConfig, cache: Arc<SharedStateCache<S>>) -> Self {
+        Self { config, cache }
+    }
+
+    pub fn cache(&self) -> &Arc<SharedStateCache<S>> {
+        &self.cache
+    }
+}
+
+impl<S: State> Solver<S> for SharedCachingSession<S> {
+    fn name(&self) -> &str {
+        "caching"
+    }
+
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    fn evaluate_with_config(&self, state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+        threads::evaluate_position_without_helpers(state, self.cache.clone(), config)
+    }
+}
+
+pub type SolverFactory<S> = Box<dyn Fn(SolverConfig) -> Box<dyn Solver<S>> + Send + Sync>;
+
+pub struct SolverRegistry<S: State> {
+    factories: Vec<(String, SolverFactory<S>)>,
+}
+
+impl<S: State> SolverRegistry<S> {
+    pub fn empty() -> Self {
+        Self {
+            factories: vec![],
+        }
+    }
+
+    pub fn with_default_solvers() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register("naive", |config| Box::new(NaiveSolver::new(config)));
+        registry.register("caching", |config| Box::new(CachingSolver::new(config)));
+        registry.register("threads", |config| Box::new(ThreadsSolver::new(config)));
+
+        registry
+    }
+
+    // registering an existing name replaces the previous factory
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(SolverConfig) -> Box<dyn Solver<S>> + Send + Sync + 'static,
+    {
+        self.factories.retain(|(registered_name, _)| registered_name != name);
+        self.factories.push((name.to_string(), Box::new(factory)));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn create(&self, name: &str, config: SolverConfig) -> Option<Box<dyn Solver<S>>> {
+        self.factories
+            .iter()
+            .find(|(registered_name, _)| registered_name == name)
+            .map(|(_, factory)| factory(config))
+    }
+
+    pub fn create_all(&self, config: SolverConfig) -> Vec<Box<dyn Solver<S>>> {
+        self.factories
+            .iter()
+            .map(|(_, factory)| factory(config.clone()))
+            .collect()
+    }
+}
+
+impl<S: State> Default for SolverRegistry<S> {
+    fn default() -> Self {
+        Self::with_default_solvers()
+    }
+}
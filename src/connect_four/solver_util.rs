@@ -1,3 +1,17 @@
+use std::cmp::max;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::connect_four::budget::{BudgetTracker, SearchBudget};
+use crate::connect_four::checkpoint::Checkpointer;
+use crate::connect_four::progress::ProgressTracker;
+use crate::connect_four::search_stats::SearchStats;
+use crate::connect_four::solver::SolverConfig;
+use crate::connect_four::state::State;
+use crate::connect_four::tablebase::Tablebase;
+use crate::connect_four::trace::{SearchTree, TreeTracer};
+
 pub const WORST_EVAL: i32 = -18;
 pub const DRAW: i32 = 0;
 pub const BEST_EVAL: i32 = 18;
@@ -10,16 +24,256 @@ pub const SECOND_PLAYER: char = 'O';
 pub const EMPTY_CELL: char = ' ';
 
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    pub fn from_eval(eval: i32) -> Self {
+        match eval.signum() {
+            1 => Outcome::Win,
+            0 => Outcome::Draw,
+            _ => Outcome::Loss,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScoreBound {
+    Exact,
+    Lower,
+}
+
 pub struct EvaluatePositionReturn {
     pub eval: i32,
     pub states_evaluated: usize,
+    pub bound: ScoreBound,
+    pub stats: Option<SearchStats>,
+    pub trace: Option<SearchTree>,
+    // the first checkpoint that could not be written; the search goes on without it
+    pub checkpoint_error: Option<io::Error>,
 }
 
 impl EvaluatePositionReturn {
     pub fn new(eval: i32, states_evaluated: usize) -> EvaluatePositionReturn {
+        Self::bounded(eval, states_evaluated, ScoreBound::Exact)
+    }
+
+    pub fn bounded(eval: i32, states_evaluated: usize, bound: ScoreBound) -> EvaluatePositionReturn {
         EvaluatePositionReturn {
             eval,
-            states_evaluated
+            states_evaluated,
+            bound,
+            stats: None,
+            trace: None,
+            checkpoint_error: None,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.bound == ScoreBound::Exact
+    }
+}
+
+pub struct SearchContext {
+    pub states_evaluated: usize,
+    pub stats: Option<SearchStats>,
+    budget: BudgetTracker,
+    tablebase: Option<Arc<Tablebase>>,
+    progress: Option<ProgressTracker>,
+    trace: Option<TreeTracer>,
+    checkpoint: Option<Checkpointer>,
+    started: Instant,
+}
+
+impl SearchContext {
+    pub fn new(budget: &SearchBudget) -> Self {
+        Self {
+            states_evaluated: 0,
+            stats: None,
+            budget: budget.start(),
+            tablebase: None,
+            progress: None,
+            trace: None,
+            checkpoint: None,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn from_config(config: &SolverConfig, root_ply: usize) -> Self {
+        let mut ctx = Self::new(&config.budget).collecting_stats(config.collect_stats, root_ply);
+        ctx.tablebase = config.tablebase.clone();
+        ctx.progress = config.progress.clone().map(ProgressTracker::new);
+        ctx.trace = config.trace.map(|trace| TreeTracer::new(trace, root_ply));
+        ctx
+    }
+
+    // the context of a search's root, which also writes the checkpoint
+    pub fn for_root<S: State>(config: &SolverConfig, state: &S) -> Self {
+        let mut ctx = Self::from_config(config, state.moves_made());
+        ctx.checkpoint = config.checkpoint.as_ref().map(|checkpoint| Checkpointer::new(checkpoint, state));
+        ctx
+    }
+
+    pub fn collecting_stats(mut self, collect_stats: bool, root_ply: usize) -> Self {
+        self.stats = collect_stats.then(|| SearchStats::new(root_ply));
+        self
+    }
+
+    pub fn out_of_budget(&mut self) -> bool {
+        self.budget.is_exhausted(self.states_evaluated)
+    }
+
+    pub fn probe_tablebase<S: State>(&self, state: &S) -> Option<i32> {
+        self.tablebase.as_ref()?.probe(state)
+    }
+
+    pub fn count_node(&mut self, ply: usize) {
+        self.states_evaluated += 1;
+
+        if let Some(stats) = &mut self.stats {
+            stats.record_node(ply);
         }
     }
+
+    pub fn start_root_move(&mut self, col: usize, window: (i32, i32)) {
+        if let Some(progress) = &mut self.progress {
+            progress.start_root_move(col, window);
+        }
+
+        if let Some(checkpoint) = &mut self.checkpoint {
+            checkpoint.start_root_move(col);
+        }
+    }
+
+    // `eval` is the root move's result from the child's side, None when the search was interrupted
+    pub fn finish_root_move(&mut self, col: usize, eval: Option<i32>) {
+        if let (Some(checkpoint), Some(eval)) = (&mut self.checkpoint, eval) {
+            checkpoint.finish_root_move(col, -eval);
+        }
+    }
+
+    // the root moves a resumed search has already finished, with their scores from the root's side
+    pub fn finished_root_moves(&self) -> Vec<(usize, i32)> {
+        self.checkpoint.as_ref().map_or(vec![], Checkpointer::finished_moves)
+    }
+
+    // called after counting a node; see `ProgressTracker::maybe_report`
+    pub fn report_progress(&mut self, cache_entries: impl FnOnce() -> Option<usize>) {
+        if let Some(progress) = &mut self.progress {
+            progress.maybe_report(self.states_evaluated, cache_entries);
+        }
+    }
+
+    // called after counting a node by searches with a cache to save
+    pub fn checkpoint_cache(&mut self, save_cache: impl FnOnce(&Path) -> io::Result<()>) {
+        if let Some(checkpoint) = &mut self.checkpoint {
+            checkpoint.maybe_save_cache(self.states_evaluated, save_cache);
+        }
+    }
+
+    pub fn record_cache_probe(&mut self, hit: bool) {
+        if let Some(stats) = &mut self.stats {
+            stats.record_cache_probe(hit);
+        }
+
+        if let Some(trace) = &mut self.trace
+            && hit
+        {
+            trace.mark_cache_hit();
+        }
+    }
+
+    pub fn record_cutoff(&mut self, move_index: usize) {
+        if let Some(stats) = &mut self.stats {
+            stats.record_cutoff(move_index);
+        }
+
+        if let Some(trace) = &mut self.trace {
+            trace.mark_cutoff();
+        }
+    }
+
+    // called around every child search with the child's column and window
+    pub fn trace_enter(&mut self, col: impl FnOnce() -> usize, window: (i32, i32)) {
+        if let Some(trace) = &mut self.trace {
+            trace.enter(col, window);
+        }
+    }
+
+    pub fn trace_exit(&mut self, value: Option<i32>) {
+        if let Some(trace) = &mut self.trace {
+            trace.exit(value);
+        }
+    }
+
+    pub fn finish_trace(&mut self, root_value: i32) -> Option<SearchTree> {
+        Some(self.trace.take()?.finish(root_value))
+    }
+
+    pub fn finish_checkpoint(&mut self, bound: ScoreBound, save_cache: impl FnOnce(&Path) -> io::Result<()>) -> Option<io::Error> {
+        self.checkpoint.take()?.finish(bound == ScoreBound::Exact, save_cache)
+    }
+
+    pub fn finish_stats(&mut self) -> Option<SearchStats> {
+        let mut stats = self.stats.take()?;
+        stats.wall_time = self.started.elapsed();
+        Some(stats)
+    }
+}
+
+// the column of the `move_index`th state in `state.next_states()`
+pub fn nth_move<S: State>(state: &S, move_index: usize) -> usize {
+    DEFAULT_MOVE_ORDER
+        .into_iter()
+        .filter(|&col| state.play_move(col).is_some())
+        .nth(move_index)
+        .expect("the move index comes from next_states")
+}
+
+// searches the root's children one at a time so that an interrupted search
+// can still report the best fully evaluated child as a lower bound.
+// `finished` are columns already searched with their scores, from a checkpoint.
+// `evaluate_child` gets the child's column, the child and the child's window
+pub fn search_root<S: State>(
+    state: &S,
+    finished: &[(usize, i32)],
+    mut evaluate_child: impl FnMut(usize, S, i32, i32) -> Option<i32>,
+) -> (i32, ScoreBound) {
+
+    if state.board_full() {
+        return (DRAW, ScoreBound::Exact);
+    }
+
+    let next_states: Vec<(usize, S)> = DEFAULT_MOVE_ORDER
+        .into_iter()
+        .filter_map(|col| state.play_move(col).map(|next_state| (col, next_state)))
+        .collect();
+
+    if next_states.iter().any(|(_, next_state)| next_state.is_win()) {
+        return (state.max_eval(), ScoreBound::Exact);
+    }
+
+    let mut alpha = finished.iter().fold(WORST_EVAL, |alpha, &(_, score)| max(alpha, score));
+
+    for (col, next_state) in next_states {
+
+        if alpha >= BEST_EVAL {
+            break;
+        }
+
+        if finished.iter().any(|&(finished_col, _)| finished_col == col) {
+            continue
+        }
+
+        match evaluate_child(col, next_state, -BEST_EVAL, -alpha) {
+            Some(eval) => alpha = max(alpha, -eval),
+            None => return (alpha, ScoreBound::Lower),
+        }
+    }
+
+    (alpha, ScoreBound::Exact)
 }
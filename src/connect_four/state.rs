@@ -5,6 +5,9 @@ const OPP_PIECE: u8 = b'O';
 
 pub trait State: Eq + Hash + Sized + Send + Sync + Clone + 'static {
 
+    // names the representation in files whose contents depend on it
+    const REPRESENTATION: &'static str;
+
     fn start_state() -> Self;
 
     fn is_win(&self) -> bool;
@@ -21,4 +24,10 @@ pub trait State: Eq + Hash + Sized + Send + Sync + Clone + 'static {
     fn encode(board: &Vec<String>) -> Self;
 
     fn decode(&self) -> String;
+
+    // unique per position: the player to move's pieces plus a marker above each column
+    fn key(&self) -> u64;
+
+    // the inverse of `key`, None for keys that no position has
+    fn from_key(key: u64) -> Option<Self>;
 }
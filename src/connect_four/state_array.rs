@@ -31,6 +31,9 @@ impl Hash for StateArray {
 }
 
 impl State for StateArray {
+
+    const REPRESENTATION: &'static str = "array";
+
     fn start_state() -> Self {
         Self {
             board: [EMPTY; BOARD_SIZE],
@@ -191,6 +194,86 @@ impl State for StateArray {
 
         board_str
     }
+
+    fn key(&self) -> u64 {
+        let mut key = 0;
+
+        for c in 0..COLS {
+            let mut bit = 1 << (c * (ROWS + 1));
+
+            for r in 0..ROWS {
+                let piece = self.board[Self::board_index(r, c)];
+
+                if !piece.is_occupied() {
+                    break
+                }
+
+                if piece == self.current_player {
+                    key |= bit;
+                }
+
+                bit <<= 1;
+            }
+
+            key += bit;
+        }
+
+        key
+    }
+
+    fn from_key(key: u64) -> Option<Self> {
+        if key >> (COLS * (ROWS + 1)) != 0 {
+            return None
+        }
+
+        // (cell, whether it holds a piece of the player to move)
+        let mut cells = vec![];
+
+        for c in 0..COLS {
+            let col = (key >> (c * (ROWS + 1))) & ((1 << (ROWS + 1)) - 1);
+
+            if col == 0 {
+                return None
+            }
+
+            // the highest set bit marks the column's next open cell
+            let height = (u64::BITS - 1 - col.leading_zeros()) as usize;
+
+            for r in 0..height {
+                cells.push((c * ROWS + r, (col >> r) & 1 == 1));
+            }
+        }
+
+        let mut state = Self::start_state();
+        state.moves_made = cells.len();
+        state.current_player = if (state.moves_made & 1) == 0 { FIRST } else { SECOND };
+
+        let curr_count = cells.iter().filter(|(_, is_curr)| *is_curr).count();
+
+        if state.moves_made - curr_count != curr_count + (state.moves_made & 1) {
+            return None
+        }
+
+        for &(cell, is_curr) in &cells {
+            let piece = if is_curr { state.current_player } else { state.current_player.next_player() };
+            state.board[cell] = piece;
+            state.curr_hash = piece.hash(state.curr_hash, cell);
+        }
+
+        // the last move is unknown, so one that makes `is_win` right is used: a piece of the player
+        // who just moved in a row of four if there is one, or else any of their pieces
+        for &(cell, is_curr) in &cells {
+            if !is_curr {
+                state.last_move = cell;
+
+                if state.is_win() {
+                    break;
+                }
+            }
+        }
+
+        Some(state)
+    }
 }
 
 impl StateArray {
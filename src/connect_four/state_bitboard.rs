@@ -22,6 +22,8 @@ pub struct StateBitboard {
 
 impl State for StateBitboard {
 
+    const REPRESENTATION: &'static str = "bitboard";
+
     fn start_state() -> Self {
         Self::encode(&vec![" ".repeat(COLS); ROWS])
     }
@@ -160,6 +162,46 @@ impl State for StateBitboard {
 
         board_str
     }
+
+    fn key(&self) -> u64 {
+        self.curr_pieces + self.height_map
+    }
+
+    fn from_key(key: u64) -> Option<Self> {
+        if key > BOARD_MASK {
+            return None
+        }
+
+        let mut state = Self::allocate();
+
+        for c in 0..COLS {
+            let col = (key >> (c * COL_BITS)) & COL_MASK;
+
+            if col == 0 {
+                return None
+            }
+
+            // the highest set bit marks the column's next open cell
+            let height = (u64::BITS - 1 - col.leading_zeros()) as usize;
+            let marker = 1 << (c * COL_BITS + height);
+            let occupied = (marker - 1) & (COL_MASK << (c * COL_BITS));
+
+            state.height_map |= marker;
+            state.curr_pieces |= key & occupied;
+            state.opp_pieces |= occupied & !key;
+            state.moves_made += height;
+        }
+
+        // the player to move has made as many moves as the opponent, or one fewer
+        let curr_count = state.curr_pieces.count_ones() as usize;
+        let opp_count = state.opp_pieces.count_ones() as usize;
+
+        if opp_count != curr_count + (state.moves_made & 1) {
+            return None
+        }
+
+        Some(state)
+    }
 }
 
 impl StateBitboard {
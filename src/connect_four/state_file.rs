@@ -2,16 +2,24 @@ use std::{io};
 use std::fs::File;
 use std::io::BufRead;
 use std::path::Path;
+use crate::connect_four::batch::{default_thread_count, parallel_map};
 use crate::connect_four::cache_strategy::optimal_next_state;
 use crate::connect_four::solver_util::ROWS;
 use crate::connect_four::state::State;
 use crate::connect_four::state_bitboard::StateBitboard;
 use std::io::Write;
 
-pub fn read_state_file<S: State>(depth: usize) -> io::Result<Vec<S>> {
+pub const METADATA_PREFIX: char = '#';
+const SCORE_METADATA: &str = "score:";
 
+pub fn read_state_file<S: State>(depth: usize) -> io::Result<Vec<S>> {
     let path_string = format!("positions/positions{depth}");
-    let file_path = Path::new(path_string.as_str());
+    read_states(Path::new(path_string.as_str()))
+}
+
+// lines starting with `METADATA_PREFIX` carry metadata and are skipped
+pub fn read_states<S: State, P: AsRef<Path>>(file_path: P) -> io::Result<Vec<S>> {
+
     let file = File::open(file_path)?;
     let reader = io::BufReader::new(file);
 
@@ -20,6 +28,11 @@ pub fn read_state_file<S: State>(depth: usize) -> io::Result<Vec<S>> {
 
     for line_result in reader.lines() {
         let line = line_result?;
+
+        if line.starts_with(METADATA_PREFIX) {
+            continue;
+        }
+
         curr_state.push(line);
 
         if curr_state.len() == ROWS {
@@ -34,16 +47,13 @@ pub fn read_state_file<S: State>(depth: usize) -> io::Result<Vec<S>> {
 pub fn generate_state_file(depth: usize) -> io::Result<()> {
 
     let prev_states: Vec<StateBitboard> = read_state_file(depth - 1)?;
-    let mut states = vec![];
 
-    for state in prev_states {
+    for state in &prev_states {
         println!("{state}");
-
-        let optimal_state = optimal_next_state(state);
-
-        states.push(optimal_state);
     }
 
+    let states = parallel_map(prev_states, default_thread_count(), optimal_next_state);
+
     let mut path = File::create(format!("positions/positions{depth}"))?;
 
     for state in states {
@@ -52,3 +62,47 @@ pub fn generate_state_file(depth: usize) -> io::Result<()> {
 
     Ok(())
 }
+
+// a suite is a state file where each position may be preceded by `# score: <n>`
+pub fn read_suite<S: State, P: AsRef<Path>>(file_path: P) -> io::Result<Vec<(S, Option<i32>)>> {
+
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    let mut entries = vec![];
+    let mut curr_state = vec![];
+    let mut score = None;
+
+    for line_result in reader.lines() {
+        let line = line_result?;
+
+        if let Some(metadata) = line.strip_prefix(METADATA_PREFIX) {
+            if let Some(value) = metadata.trim().strip_prefix(SCORE_METADATA) {
+                let value = value.trim();
+                score = Some(value.parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("invalid score \"{value}\""))
+                })?);
+            }
+
+            continue;
+        }
+
+        curr_state.push(line);
+
+        if curr_state.len() == ROWS {
+            entries.push((S::encode(&curr_state), score.take()));
+            curr_state = vec![];
+        }
+    }
+
+    Ok(entries)
+}
+
+pub fn write_suite<S: State, W: Write>(writer: &mut W, entries: &[(S, i32)]) -> io::Result<()> {
+    for (state, score) in entries {
+        writeln!(writer, "{METADATA_PREFIX} {SCORE_METADATA} {score}")?;
+        write!(writer, "{}", state.decode())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::connect_four::solver_util::{BOARD_SIZE, COLS, DRAW, ROWS};
+use crate::connect_four::state::State;
+
+const MAGIC: &[u8; 4] = b"C4TB";
+const VERSION: u8 = 2;
+
+pub struct Tablebase {
+    max_empty_cells: usize,
+    // the keys are only meaningful for the representation that made them
+    representation: &'static str,
+    // sorted by key
+    entries: Vec<(u64, i8)>,
+}
+
+impl Tablebase {
+
+    // solves every non-terminal position with at most `max_empty_cells` empty cells
+    // that can be reached from `roots`, working backwards from the end of the game
+    pub fn generate<S: State>(roots: &[S], max_empty_cells: usize) -> Self {
+        // one set per ply, so that a position reached by several move orders is only expanded once
+        let mut layers: Vec<HashSet<S>> = vec![HashSet::new(); BOARD_SIZE + 1];
+
+        for root in roots {
+            if !root.is_win() && !root.board_full() {
+                layers[root.moves_made()].insert(root.clone());
+            }
+        }
+
+        for ply in 0..BOARD_SIZE {
+            let (earlier, later) = layers.split_at_mut(ply + 1);
+
+            for state in &earlier[ply] {
+                for next_state in state.next_states() {
+                    if !next_state.is_win() && !next_state.board_full() {
+                        later[0].insert(next_state);
+                    }
+                }
+            }
+
+            // plies before the endgame are only needed to reach the next ply
+            if BOARD_SIZE - ply > max_empty_cells {
+                earlier[ply] = HashSet::new();
+            }
+        }
+
+        let mut scores: HashMap<u64, i8> = HashMap::with_capacity(layers.iter().map(HashSet::len).sum());
+
+        for state in layers.iter().rev().flatten() {
+            let mut score = i32::MIN;
+
+            for next_state in state.next_states() {
+                let eval = if next_state.is_win() {
+                    state.max_eval()
+                } else if next_state.board_full() {
+                    DRAW
+                } else {
+                    -(scores[&next_state.key()] as i32)
+                };
+
+                score = score.max(eval);
+            }
+
+            scores.insert(state.key(), score as i8);
+        }
+
+        let mut entries: Vec<(u64, i8)> = scores.into_iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        Self {
+            max_empty_cells,
+            representation: S::REPRESENTATION,
+            entries,
+        }
+    }
+
+    pub fn max_empty_cells(&self) -> usize {
+        self.max_empty_cells
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn probe<S: State>(&self, state: &S) -> Option<i32> {
+        if BOARD_SIZE - state.moves_made() > self.max_empty_cells {
+            return None
+        }
+
+        let key = state.key();
+
+        self.entries
+            .binary_search_by_key(&key, |(entry_key, _)| *entry_key)
+            .ok()
+            .map(|index| self.entries[index].1 as i32)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(&file_header(self.representation))?;
+        writer.write_all(&[self.max_empty_cells as u8])?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+
+        for (key, score) in &self.entries {
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&score.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn load<S: State, P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let expected_header = file_header(S::REPRESENTATION);
+
+        // a file too short for a header is as wrong as one with a different header
+        let mut header = vec![];
+        (&mut reader).take(expected_header.len() as u64).read_to_end(&mut header)?;
+
+        if header != expected_header {
+            let message = format!("not a tablebase for a {ROWS}x{COLS} board with the {} representation", S::REPRESENTATION);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let mut max_empty_cells = [0];
+        reader.read_exact(&mut max_empty_cells).map_err(truncated_tablebase)?;
+
+        let mut len = [0; 8];
+        reader.read_exact(&mut len).map_err(truncated_tablebase)?;
+
+        let len = u64::from_le_bytes(len) as usize;
+        // a damaged length should fail on the missing entries rather than on the allocation
+        let mut entries: Vec<(u64, i8)> = Vec::with_capacity(len.min(1 << 26));
+        let mut entry = [0; 9];
+
+        for _ in 0..len {
+            reader.read_exact(&mut entry).map_err(truncated_tablebase)?;
+            let key = u64::from_le_bytes(entry[..8].try_into().unwrap());
+
+            // probing is a binary search, which would silently miss entries out of order
+            if entries.last().is_some_and(|&(previous_key, _)| previous_key >= key) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "tablebase entries are not sorted by key"));
+            }
+
+            entries.push((key, entry[8] as i8));
+        }
+
+        Ok(Self {
+            max_empty_cells: max_empty_cells[0] as usize,
+            representation: S::REPRESENTATION,
+            entries,
+        })
+    }
+}
+
+fn file_header(representation: &str) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.extend([VERSION, ROWS as u8, COLS as u8, representation.len() as u8]);
+    header.extend(representation.as_bytes());
+    header
+}
+
+fn truncated_tablebase(err: io::Error) -> io::Error {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        io::Error::new(io::ErrorKind::InvalidData, "truncated tablebase")
+    } else {
+        err
+    }
+}
@@ -1,40 +1,68 @@
 use std::cmp::{max, min};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::thread::JoinHandle;
 use dashmap::DashMap;
-use crate::connect_four::solver_util::{EvaluatePositionReturn, DRAW, WORST_EVAL, BEST_EVAL};
+use crate::connect_four::budget::{CancelToken, SearchBudget};
+use crate::connect_four::solver_util::{search_root, EvaluatePositionReturn, SearchContext, DRAW, WORST_EVAL, BEST_EVAL};
 use crate::connect_four::naive;
+use crate::connect_four::search_stats::SearchStats;
+use crate::connect_four::solver::SolverConfig;
 use crate::connect_four::state::State;
 
 
 const MAX_CACHED_DEPTH: usize = 35;
 
 struct ThreadContext<S: State> {
-    states_evaluated: usize,
-    terminate_signal: Arc<AtomicBool>,
+    search: SearchContext,
     cache: Arc<SharedStateCache<S>>,
 }
 
+impl<S: State> ThreadContext<S> {
+    fn fetch_alpha_bound(&mut self, state: &S) -> i32 {
+        let bound = self.cache.get_alpha_bound(state);
+        self.search.record_cache_probe(bound.is_some());
+        bound.unwrap_or(WORST_EVAL)
+    }
+
+    fn fetch_beta_bound(&mut self, state: &S) -> i32 {
+        let bound = self.cache.get_beta_bound(state);
+        self.search.record_cache_probe(bound.is_some());
+        bound.unwrap_or(BEST_EVAL)
+    }
+}
+
 struct HelperThreadHandler {
-    join_handle: JoinHandle<usize>,
-    terminate_signal: Arc<AtomicBool>,
+    join_handle: JoinHandle<(usize, Option<SearchStats>)>,
+    terminate_signal: CancelToken,
 }
 
-struct SharedStateCache<S: State> {
+pub struct SharedStateCache<S: State> {
     alpha_cache: DashMap<S, i32>,
     beta_cache: DashMap<S, i32>,
 }
 
 impl<S: State> SharedStateCache<S> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             alpha_cache: DashMap::new(),
             beta_cache: DashMap::new(),
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.alpha_cache.len() + self.beta_cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alpha_cache.is_empty() && self.beta_cache.is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.alpha_cache.clear();
+        self.beta_cache.clear();
+    }
+
     fn insert_alpha_bound(&self, state: S, bound: i32) {
         self.alpha_cache.insert(state, bound);
     }
@@ -43,12 +71,18 @@ impl<S: State> SharedStateCache<S> {
         self.beta_cache.insert(state, bound);
     }
 
-    fn fetch_alpha_bound(&self, state: &S) -> i32 {
-        *self.alpha_cache.get(state).as_deref().unwrap_or(&WORST_EVAL)
+    fn get_alpha_bound(&self, state: &S) -> Option<i32> {
+        self.alpha_cache.get(state).as_deref().copied()
     }
 
-    fn fetch_beta_bound(&self, state: &S) -> i32 {
-        *self.beta_cache.get(state).as_deref().unwrap_or(&BEST_EVAL)
+    fn get_beta_bound(&self, state: &S) -> Option<i32> {
+        self.beta_cache.get(state).as_deref().copied()
+    }
+}
+
+impl<S: State> Default for SharedStateCache<S> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -59,22 +93,27 @@ fn evaluate_position_rec<S: State>(
     ctx: &mut ThreadContext<S>,
 ) -> Option<i32> {
 
-    if ctx.terminate_signal.load(Ordering::Relaxed) {
-        return None
+    if state.moves_made() > MAX_CACHED_DEPTH {
+        return naive::evaluate_position_rec(state, alpha, beta, &mut ctx.search);
     }
 
-    if state.moves_made() > MAX_CACHED_DEPTH {
-        return Some(naive::evaluate_position_rec(state, alpha, beta, &mut ctx.states_evaluated));
+    if ctx.search.out_of_budget() {
+        return None
     }
 
-    ctx.states_evaluated += 1;
+    ctx.search.count_node(state.moves_made());
+    ctx.search.report_progress(|| Some(ctx.cache.len()));
 
     if state.board_full() {
         return Some(DRAW);
     }
 
-    alpha = max(alpha, ctx.cache.fetch_alpha_bound(&state));
-    beta = min(beta, ctx.cache.fetch_beta_bound(&state));
+    if let Some(eval) = ctx.search.probe_tablebase(&state) {
+        return Some(eval);
+    }
+
+    alpha = max(alpha, ctx.fetch_alpha_bound(&state));
+    beta = min(beta, ctx.fetch_beta_bound(&state));
 
     let next_states = state.next_states();
 
@@ -84,10 +123,16 @@ fn evaluate_position_rec<S: State>(
             return Some(state.max_eval());
         }
 
-        alpha = max(alpha, -ctx.cache.fetch_beta_bound(next_state));
+        alpha = max(alpha, -ctx.fetch_beta_bound(next_state));
     }
 
-    for next_state in next_states {
+    // the cached bounds alone decide the result. searching on with the closed window would
+    // cut off at the first move and cache the window's edge as if it were a proven bound
+    if alpha >= beta {
+        return Some(alpha);
+    }
+
+    for (move_index, next_state) in next_states.into_iter().enumerate() {
 
         let eval = -evaluate_position_rec(
             next_state,
@@ -99,6 +144,7 @@ fn evaluate_position_rec<S: State>(
         alpha = max(alpha, eval);
 
         if alpha >= beta {
+            ctx.search.record_cutoff(move_index);
             ctx.cache.insert_alpha_bound(state, alpha);
             return Some(alpha);
         }
@@ -109,23 +155,65 @@ fn evaluate_position_rec<S: State>(
 }
 
 pub fn evaluate_position<S: State>(state: S) -> EvaluatePositionReturn {
+    evaluate_position_with_budget(state, &SearchBudget::unlimited())
+}
 
-    let cache = Arc::new(SharedStateCache::new());
+pub fn evaluate_position_with_budget<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()))
+}
+
+pub fn evaluate_position_with_stats<S: State>(state: S, budget: &SearchBudget) -> EvaluatePositionReturn {
+    evaluate_position_with_config(state, &SolverConfig::default().with_budget(budget.clone()).with_stats(true))
+}
+
+pub fn evaluate_position_with_config<S: State>(state: S, config: &SolverConfig) -> EvaluatePositionReturn {
+    evaluate_position_with_cache(state, Arc::new(SharedStateCache::new()), config)
+}
+
+pub fn evaluate_position_with_cache<S: State>(
+    state: S,
+    cache: Arc<SharedStateCache<S>>,
+    config: &SolverConfig,
+) -> EvaluatePositionReturn {
+    evaluate_position_with_helpers(state, cache, config, true)
+}
+
+// searches on the calling thread alone, so that many searches can share one cache at once
+pub fn evaluate_position_without_helpers<S: State>(
+    state: S,
+    cache: Arc<SharedStateCache<S>>,
+    config: &SolverConfig,
+) -> EvaluatePositionReturn {
+    evaluate_position_with_helpers(state, cache, config, false)
+}
+
+fn evaluate_position_with_helpers<S: State>(
+    state: S,
+    cache: Arc<SharedStateCache<S>>,
+    config: &SolverConfig,
+    helpers: bool,
+) -> EvaluatePositionReturn {
+
+    let root_ply = state.moves_made();
     let mut handlers = vec![];
+    let helper_states = if helpers { state.next_states() } else { vec![] };
 
-    for next_state in state.next_states() {
-        let terminate_signal = Arc::new(AtomicBool::new(false));
+    for next_state in helper_states {
+        let terminate_signal = CancelToken::new();
+        let mut helper_config = config.clone().with_budget(SearchBudget::unlimited().with_cancel_token(terminate_signal.clone()));
+
+        // only the main thread reports progress, so reports count its nodes alone
+        helper_config.progress = None;
 
         let mut ctx = ThreadContext {
-            terminate_signal: terminate_signal.clone(),
-            states_evaluated: 0,
+            search: SearchContext::from_config(&helper_config, root_ply),
             cache: cache.clone()
         };
 
         let handle = thread::spawn(move || {
             const HELPER_THREAD_BETA: i32 = 1; // this significantly affects performance
             evaluate_position_rec(next_state, -HELPER_THREAD_BETA, HELPER_THREAD_BETA, &mut ctx);
-            ctx.states_evaluated
+            (ctx.search.states_evaluated, ctx.search.finish_stats())
         });
 
         handlers.push(HelperThreadHandler {
@@ -135,23 +223,41 @@ pub fn evaluate_position<S: State>(state: S) -> EvaluatePositionReturn {
     }
 
     let mut master_thread_ctx = ThreadContext {
-        terminate_signal: Arc::new(AtomicBool::new(false)),
-        states_evaluated: 0,
+        search: SearchContext::for_root(config, &state),
         cache
     };
+    master_thread_ctx.search.count_node(root_ply);
 
-    let eval = evaluate_position_rec(state, WORST_EVAL, BEST_EVAL, &mut master_thread_ctx).unwrap();
+    let (eval, bound) = search_root(&state, &master_thread_ctx.search.finished_root_moves(), |col, next_state, alpha, beta| {
+        master_thread_ctx.search.start_root_move(col, (-beta, -alpha));
+        let eval = evaluate_position_rec(next_state, alpha, beta, &mut master_thread_ctx);
+        master_thread_ctx.search.finish_root_move(col, eval);
+        eval
+    });
 
     for handler in &handlers {
-        handler.terminate_signal.store(true, Ordering::Relaxed);
+        handler.terminate_signal.cancel();
     }
 
-    let mut states_evaluated = master_thread_ctx.states_evaluated;
+    let mut states_evaluated = master_thread_ctx.search.states_evaluated;
+    let mut stats = master_thread_ctx.search.finish_stats();
 
     for handler in handlers {
-        let a = handler.join_handle.join();
-        states_evaluated += a.unwrap()
+        let (helper_states_evaluated, helper_stats) = handler.join_handle.join().unwrap();
+        states_evaluated += helper_states_evaluated;
+
+        if let (Some(stats), Some(helper_stats)) = (&mut stats, &helper_stats) {
+            stats.merge(helper_stats);
+        }
+    }
+
+    if let Some(stats) = &mut stats {
+        // the shared cache only grows during a search, so its final size is the peak
+        stats.record_cache_sizes(master_thread_ctx.cache.alpha_cache.len(), master_thread_ctx.cache.beta_cache.len());
     }
 
-    EvaluatePositionReturn::new(eval, states_evaluated)
+    let mut ret = EvaluatePositionReturn::bounded(eval, states_evaluated, bound);
+    ret.stats = stats;
+    ret.checkpoint_error = master_thread_ctx.search.finish_checkpoint(bound, |_| Ok(()));
+    ret
 }
@@ -0,0 +1,418 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use crate::connect_four::budget::SearchBudget;
+use crate::connect_four::heuristic::StaticEvaluation;
+use crate::connect_four::mcts::MctsBudget;
+use crate::connect_four::move_string::parse_position;
+use crate::connect_four::player::{DepthLimitedPlayer, MctsPlayer, Player, RandomPlayer, SolverPlayer};
+use crate::connect_four::solver::{CachingSession, SolverConfig};
+use crate::connect_four::state::State;
+use crate::connect_four::state_file::METADATA_PREFIX;
+
+pub const DEFAULT_EXACT_TIME_LIMIT: Duration = Duration::from_millis(200);
+const EXACT_FALLBACK_DEPTH: usize = 42;
+
+// for 95% confidence intervals
+const CONFIDENCE_Z: f64 = 1.96;
+
+pub type PlayerFactory<S> = Box<dyn Fn(u64) -> Box<dyn Player<S>>>;
+
+// "exact", "exact:<ms per search>", "depth:<n>", "mcts:<iterations>", "mcts:<ms>ms" or "random"
+pub fn create_player<S: StaticEvaluation>(spec: &str, seed: u64) -> Option<Box<dyn Player<S>>> {
+    let (kind, arg) = match spec.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec, None),
+    };
+
+    match (kind, arg) {
+        ("exact", arg) => {
+            let time_limit = match arg {
+                Some(millis) => Duration::from_millis(millis.parse().ok()?),
+                None => DEFAULT_EXACT_TIME_LIMIT,
+            };
+
+            let config = SolverConfig::default().with_budget(SearchBudget::unlimited().with_time_limit(time_limit));
+            let fallback = DepthLimitedPlayer::new(EXACT_FALLBACK_DEPTH).with_time_limit(time_limit);
+
+            Some(Box::new(SolverPlayer::new(Box::new(CachingSession::new(config))).with_fallback(Box::new(fallback))))
+        },
+        ("depth", Some(depth)) => Some(Box::new(DepthLimitedPlayer::new(depth.parse().ok()?))),
+        ("mcts", Some(budget)) => {
+            let budget = match budget.strip_suffix("ms") {
+                Some(millis) => MctsBudget::Time(Duration::from_millis(millis.parse().ok()?)),
+                None => MctsBudget::Iterations(budget.parse().ok()?),
+            };
+
+            Some(Box::new(MctsPlayer::new(seed, budget)))
+        },
+        ("random", None) => Some(Box::new(RandomPlayer::new(seed))),
+        _ => None,
+    }
+}
+
+// one opening per line as a move string or grid, skipping blank and '#' lines
+pub fn read_book<S: State, P: AsRef<Path>>(path: P) -> io::Result<Vec<S>> {
+    let mut openings = vec![];
+
+    for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(METADATA_PREFIX) {
+            continue;
+        }
+
+        match parse_position::<S>(line) {
+            Some(state) if !state.is_win() && !state.board_full() => openings.push(state),
+            _ => {
+                let message = format!("line {}: \"{line}\" is not an unfinished position", index + 1);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            },
+        }
+    }
+
+    Ok(openings)
+}
+
+struct Entrant<S: State> {
+    name: String,
+    factory: PlayerFactory<S>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TournamentGame {
+    // entrant indices; `first` is the side to move in the opening
+    pub first: usize,
+    pub second: usize,
+    pub opening: usize,
+    // X moves first from the empty board, so `first` has the O stones in openings with an odd number of moves
+    pub first_plays_x: bool,
+    pub moves: Vec<usize>,
+    pub winner: Option<usize>,
+    // the loser returned no move or an illegal one
+    pub forfeit: bool,
+}
+
+// every pair of entrants plays every opening twice, once from each side. each game gets fresh
+// players, so caches and random number generators do not carry over between games
+pub struct Tournament<S: State> {
+    entrants: Vec<Entrant<S>>,
+    openings: Vec<S>,
+    seed: u64,
+}
+
+impl<S: State> Tournament<S> {
+    pub fn new(openings: Vec<S>, seed: u64) -> Self {
+        Self {
+            entrants: vec![],
+            openings,
+            seed,
+        }
+    }
+
+    // the factory gets a seed for players that use randomness
+    pub fn with_entrant<F>(mut self, name: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn(u64) -> Box<dyn Player<S>> + 'static,
+    {
+        self.entrants.push(Entrant {
+            name: name.into(),
+            factory: Box::new(factory),
+        });
+
+        self
+    }
+
+    pub fn game_count(&self) -> usize {
+        let pairs = self.entrants.len() * self.entrants.len().saturating_sub(1) / 2;
+        pairs * self.openings.len() * 2
+    }
+
+    pub fn entrant_name(&self, index: usize) -> &str {
+        &self.entrants[index].name
+    }
+
+    pub fn run(&self, mut on_game: impl FnMut(&TournamentGame)) -> TournamentReport {
+        let start = Instant::now();
+        let mut report = TournamentReport::new(self.entrants.iter().map(|entrant| entrant.name.clone()).collect());
+        let mut game_index = 0;
+
+        for a in 0..self.entrants.len() {
+            for b in a + 1..self.entrants.len() {
+                for (opening, state) in self.openings.iter().enumerate() {
+                    for (first, second) in [(a, b), (b, a)] {
+                        let seed = self.seed.wrapping_add(2 * game_index);
+                        let mut players = [(self.entrants[first].factory)(seed), (self.entrants[second].factory)(seed.wrapping_add(1))];
+
+                        let game = play_game(state, &mut players, [first, second], opening, &mut report);
+                        report.add_game(&game);
+                        on_game(&game);
+
+                        game_index += 1;
+                    }
+                }
+            }
+        }
+
+        report.wall_time = start.elapsed();
+        report
+    }
+}
+
+fn play_game<S: State>(
+    opening: &S,
+    players: &mut [Box<dyn Player<S>>; 2],
+    entrants: [usize; 2],
+    opening_index: usize,
+    report: &mut TournamentReport,
+) -> TournamentGame {
+
+    let mut state = opening.clone();
+    let mut moves = vec![];
+    let mut side = 0;
+
+    let (winner, forfeit) = loop {
+        if state.board_full() {
+            break (None, false);
+        }
+
+        let start = Instant::now();
+        let col = players[side].choose_move(&state);
+        report.engines[entrants[side]].add_move(start.elapsed());
+
+        let Some(next_state) = col.and_then(|col| state.play_move(col)) else {
+            break (Some(entrants[1 - side]), true);
+        };
+
+        moves.push(col.unwrap());
+
+        if next_state.is_win() {
+            break (Some(entrants[side]), false);
+        }
+
+        state = next_state;
+        side = 1 - side;
+    };
+
+    TournamentGame {
+        first: entrants[0],
+        second: entrants[1],
+        opening: opening_index,
+        first_plays_x: opening.moves_made().is_multiple_of(2),
+        moves,
+        winner,
+        forfeit,
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    // the fraction of points won, counting a draw as half a win
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5
+        }
+
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    // the 95% Wilson interval of the score, which treats every game as a single win or loss and so
+    // is somewhat wider than it needs to be when there are draws
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let games = self.games() as f64;
+
+        if games == 0.0 {
+            return (0.0, 1.0)
+        }
+
+        let score = self.score();
+        let z2 = CONFIDENCE_Z * CONFIDENCE_Z;
+        let denominator = 1.0 + z2 / games;
+        let center = (score + z2 / (2.0 * games)) / denominator;
+        let half_width = CONFIDENCE_Z * (score * (1.0 - score) / games + z2 / (4.0 * games * games)).sqrt() / denominator;
+
+        ((center - half_width).max(0.0), (center + half_width).min(1.0))
+    }
+
+    fn add(&mut self, won: Option<bool>) {
+        match won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let (low, high) = self.confidence_interval();
+        let mut record = serializer.serialize_struct("Record", 6)?;
+
+        record.serialize_field("wins", &self.wins)?;
+        record.serialize_field("draws", &self.draws)?;
+        record.serialize_field("losses", &self.losses)?;
+        record.serialize_field("score", &self.score())?;
+        record.serialize_field("confidence_low", &low)?;
+        record.serialize_field("confidence_high", &high)?;
+        record.end()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EngineReport {
+    pub name: String,
+    pub record: Record,
+    pub moves: usize,
+    #[serde(rename = "think_time_secs", serialize_with = "serialize_secs")]
+    pub think_time: Duration,
+    #[serde(rename = "longest_move_secs", serialize_with = "serialize_secs")]
+    pub longest_move: Duration,
+}
+
+impl EngineReport {
+    pub fn average_move_time(&self) -> Duration {
+        self.think_time.checked_div(self.moves as u32).unwrap_or_default()
+    }
+
+    fn add_move(&mut self, time: Duration) {
+        self.moves += 1;
+        self.think_time += time;
+        self.longest_move = self.longest_move.max(time);
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchupReport {
+    pub engine: String,
+    pub opponent: String,
+    // from `engine`'s side
+    pub record: Record,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TournamentReport {
+    pub engines: Vec<EngineReport>,
+    // one per pair of engines
+    pub matchups: Vec<MatchupReport>,
+    pub games: usize,
+    #[serde(rename = "wall_time_secs", serialize_with = "serialize_secs")]
+    pub wall_time: Duration,
+}
+
+impl TournamentReport {
+    fn new(names: Vec<String>) -> Self {
+        let mut matchups = vec![];
+
+        for (a, engine) in names.iter().enumerate() {
+            for opponent in &names[a + 1..] {
+                matchups.push(MatchupReport {
+                    engine: engine.clone(),
+                    opponent: opponent.clone(),
+                    record: Record::default(),
+                });
+            }
+        }
+
+        Self {
+            engines: names
+                .into_iter()
+                .map(|name| EngineReport {
+                    name,
+                    record: Record::default(),
+                    moves: 0,
+                    think_time: Duration::ZERO,
+                    longest_move: Duration::ZERO,
+                })
+                .collect(),
+            matchups,
+            games: 0,
+            wall_time: Duration::ZERO,
+        }
+    }
+
+    fn add_game(&mut self, game: &TournamentGame) {
+        let (a, b) = (game.first.min(game.second), game.first.max(game.second));
+        let a_won = game.winner.map(|winner| winner == a);
+
+        // matchups are stored in the order the pairs are played
+        let matchup = a * self.engines.len() - a * (a + 1) / 2 + (b - a - 1);
+
+        self.matchups[matchup].record.add(a_won);
+        self.engines[a].record.add(a_won);
+        self.engines[b].record.add(a_won.map(|a_won| !a_won));
+        self.games += 1;
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("tournament reports are always serializable")
+    }
+}
+
+fn serialize_secs<Ser: Serializer>(duration: &Duration, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+fn format_score(record: &Record) -> String {
+    let (low, high) = record.confidence_interval();
+    format!("{:5.1}%  [{:5.1}%, {:5.1}%]", record.score() * 100.0, low * 100.0, high * 100.0)
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.engines.iter().map(|engine| engine.name.len()).max().unwrap_or(0).max("engine".len());
+
+        writeln!(f, "{:width$}  games     +     =     -   score   95% interval      ms/move   max ms", "engine")?;
+
+        for engine in &self.engines {
+            let record = &engine.record;
+
+            writeln!(
+                f,
+                "{:width$}  {:5} {:5} {:5} {:5}  {}  {:8.1} {:8.1}",
+                engine.name,
+                record.games(),
+                record.wins,
+                record.draws,
+                record.losses,
+                format_score(record),
+                engine.average_move_time().as_secs_f64() * 1000.0,
+                engine.longest_move.as_secs_f64() * 1000.0,
+            )?;
+        }
+
+        let matchups: Vec<String> = self
+            .matchups
+            .iter()
+            .map(|matchup| format!("{} vs {}", matchup.engine, matchup.opponent))
+            .collect();
+
+        let width = matchups.iter().map(String::len).max().unwrap_or(0).max("matchup".len());
+
+        writeln!(f)?;
+        writeln!(f, "{:width$}      +     =     -   score   95% interval", "matchup")?;
+
+        for (name, matchup) in matchups.iter().zip(&self.matchups) {
+            let record = &matchup.record;
+            writeln!(f, "{name:width$}  {:5} {:5} {:5}  {}", record.wins, record.draws, record.losses, format_score(record))?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{} games in {:.3?}", self.games, self.wall_time)
+    }
+}
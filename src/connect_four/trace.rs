@@ -0,0 +1,195 @@
+use std::fmt::Write;
+use serde::{Serialize, Serializer};
+use crate::connect_four::solver_util::{BEST_EVAL, WORST_EVAL};
+
+pub const DEFAULT_TRACE_MAX_DEPTH: usize = 4;
+pub const DEFAULT_TRACE_MAX_NODES: usize = 10_000;
+
+#[derive(Copy, Clone, Debug)]
+pub struct TraceConfig {
+    // plies below the root
+    pub max_depth: usize,
+    pub max_nodes: usize,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_TRACE_MAX_DEPTH,
+            max_nodes: DEFAULT_TRACE_MAX_NODES,
+        }
+    }
+}
+
+// windows and values are from the perspective of the player to move at the node
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+    pub ply: usize,
+    // the column played to reach the node, None for the root
+    #[serde(serialize_with = "serialize_column")]
+    pub col: Option<usize>,
+    pub alpha: i32,
+    pub beta: i32,
+    // None when the budget ran out before the node was finished
+    pub value: Option<i32>,
+    // the search stopped at this node after a move reached beta
+    pub cutoff: bool,
+    // a cached bound was found while searching the node
+    pub cache_hit: bool,
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchTree {
+    // the root is the first node
+    pub nodes: Vec<TraceNode>,
+    // nodes within the depth limit were left out to stay under the node cap
+    pub truncated: bool,
+}
+
+fn serialize_column<Ser: Serializer>(col: &Option<usize>, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    match col {
+        Some(col) => serializer.serialize_some(&(col + 1)),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl SearchTree {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("search trees are always serializable")
+    }
+
+    // cutoffs are drawn in red and nodes with cache hits are filled
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search {\n    node [shape=box, fontname=\"monospace\"];\n");
+
+        for node in &self.nodes {
+            let col = node.col.map_or("root".to_string(), |col| format!("col {}", col + 1));
+            let value = node.value.map_or("?".to_string(), |value| value.to_string());
+            let mut attributes = format!("label=\"{col}\\n[{}, {}]\\n= {value}\"", node.alpha, node.beta);
+
+            if node.cutoff {
+                attributes.push_str(", color=red");
+            }
+
+            if node.cache_hit {
+                attributes.push_str(", style=filled, fillcolor=lightblue");
+            }
+
+            writeln!(dot, "    n{} [{attributes}];", node.id).unwrap();
+
+            if let Some(parent) = node.parent {
+                writeln!(dot, "    n{parent} -> n{};", node.id).unwrap();
+            }
+        }
+
+        if self.truncated {
+            dot.push_str("    truncated [shape=plaintext, label=\"node cap reached\"];\n");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// records the tree as the search enters and leaves nodes. nodes past the depth limit or the
+// node cap are still entered and left, but only as placeholders on the stack
+pub struct TreeTracer {
+    config: TraceConfig,
+    tree: SearchTree,
+    // the node being searched and its ancestors, None for nodes that are not recorded
+    stack: Vec<Option<usize>>,
+}
+
+impl TreeTracer {
+    pub fn new(config: TraceConfig, root_ply: usize) -> Self {
+        let root = TraceNode {
+            id: 0,
+            parent: None,
+            ply: root_ply,
+            col: None,
+            alpha: WORST_EVAL,
+            beta: BEST_EVAL,
+            value: None,
+            cutoff: false,
+            cache_hit: false,
+            children: vec![],
+        };
+
+        Self {
+            config,
+            tree: SearchTree {
+                nodes: vec![root],
+                truncated: false,
+            },
+            stack: vec![Some(0)],
+        }
+    }
+
+    // `col` is only called for nodes that are recorded
+    pub fn enter(&mut self, col: impl FnOnce() -> usize, window: (i32, i32)) {
+        let parent = self.stack.last().copied().flatten();
+        let depth = self.stack.len();
+
+        let id = match parent {
+            Some(parent) if depth <= self.config.max_depth => {
+                if self.tree.nodes.len() >= self.config.max_nodes {
+                    self.tree.truncated = true;
+                    None
+                } else {
+                    let id = self.tree.nodes.len();
+                    let ply = self.tree.nodes[parent].ply + 1;
+
+                    self.tree.nodes[parent].children.push(id);
+                    self.tree.nodes.push(TraceNode {
+                        id,
+                        parent: Some(parent),
+                        ply,
+                        col: Some(col()),
+                        alpha: window.0,
+                        beta: window.1,
+                        value: None,
+                        cutoff: false,
+                        cache_hit: false,
+                        children: vec![],
+                    });
+
+                    Some(id)
+                }
+            },
+            _ => None,
+        };
+
+        self.stack.push(id);
+    }
+
+    pub fn exit(&mut self, value: Option<i32>) {
+        if let Some(Some(id)) = self.stack.pop() {
+            self.tree.nodes[id].value = value;
+        }
+    }
+
+    pub fn mark_cutoff(&mut self) {
+        if let Some(node) = self.current() {
+            node.cutoff = true;
+        }
+    }
+
+    pub fn mark_cache_hit(&mut self) {
+        if let Some(node) = self.current() {
+            node.cache_hit = true;
+        }
+    }
+
+    pub fn finish(mut self, root_value: i32) -> SearchTree {
+        self.tree.nodes[0].value = Some(root_value);
+        self.tree
+    }
+
+    fn current(&mut self) -> Option<&mut TraceNode> {
+        let id = self.stack.last().copied().flatten()?;
+        Some(&mut self.tree.nodes[id])
+    }
+}
@@ -1,23 +1,1044 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
 use std::io;
-use software_testing_project::sorts::sorts::{heapsort, mergesort, quicksort, timsort};
-use rand::{rng, Rng};
-use rand_pcg::Pcg64;
-use crate::connect_four::state_file::generate_state_file;
+use std::io::{IsTerminal, Write};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::process::{Command, ExitCode};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use software_testing_project::connect_four::analysis::analyze_game;
+use software_testing_project::connect_four::batch::{default_thread_count, solve_batch};
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::cache_strategy::{evaluate_position_with_cache, StateCache};
+use software_testing_project::connect_four::checkpoint::{CheckpointConfig, RootCheckpoint};
+use software_testing_project::connect_four::distributed::{solve_distributed, WorkerConnection, DEFAULT_SPLIT_PLY};
+use software_testing_project::connect_four::engine::Engine;
+use software_testing_project::connect_four::game_record::GameRecord;
+use software_testing_project::connect_four::progress::{ProgressObserver, SearchProgress};
+use software_testing_project::connect_four::move_string::{format_grid, format_moves, parse_position};
+use software_testing_project::connect_four::puzzle::{random_positions, search_puzzles, write_puzzle_file, write_puzzles};
+use software_testing_project::connect_four::search_stats::SearchStats;
+use software_testing_project::connect_four::server::{Server, DEFAULT_MAX_CONCURRENT_SOLVES};
+use software_testing_project::connect_four::solver::{CachingSession, Solver, SolverConfig, SolverRegistry, ThreadsSession};
+use software_testing_project::connect_four::solver_util::{EvaluatePositionReturn, Outcome, BOARD_SIZE, COLS};
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+use software_testing_project::connect_four::state_file::{generate_state_file, read_suite, write_suite};
+use software_testing_project::connect_four::tablebase::Tablebase;
+use software_testing_project::connect_four::trace::{TraceConfig, DEFAULT_TRACE_MAX_DEPTH, DEFAULT_TRACE_MAX_NODES};
+use software_testing_project::connect_four::tournament::{create_player, read_book, Tournament, TournamentGame};
 
-mod connect_four;
+const USAGE: &str = "\
+usage: software_testing_project <command> [options]
 
-fn main() -> io::Result<()> {
-    // let mut vec: Vec<i32> = (0..10000000)
-    //     .map(|_| rng().random::<i32>())
-    //     .collect();
-    //
-    // mergesort(&mut vec);
-    //
-    // println!("{:?}", vec.is_sorted());
+commands:
+  solve <position>               score a position for the player to move
+  analyze <position>             score every column of a position
+  analyze --game <file>          score every move of a recorded game
+  generate --moves <n>           write a suite of random positions with their scores
+  generate --chain <from>..<to>  extend positions/positionsN by optimal play
+  verify <suite>                 check a suite's positions against their expected scores
+  puzzles --moves <n>            find random positions where exactly one column wins
+  puzzles --suite <file>         find such positions among a suite's positions
+  engine                         speak the UCI-like engine protocol on stdin and stdout
+  serve                          answer JSON requests on stdin, one per line, keeping the cache
+  serve --listen <addr>          answer JSON requests from TCP clients sharing one cache
+  serve --unix <path>            the same on a Unix socket
+  tournament --engines <list>    play engines against each other, e.g. exact,depth:6,mcts:2000,random
+  distribute <position>          score a position by splitting its search among worker processes
 
-    for i in 16..31 {
-        generate_state_file(i)?;
+positions are move strings such as 4453, or grids of rows from the top
+separated by '/' with '.' for empty cells
+
+options:
+  --solver <name>     naive, caching or threads (default caching)
+  --time <secs>       time limit for each search, or for each position's columns together
+                      when every column is scored (analyze)
+  --nodes <n>         node limit for each search, or for each position's columns together
+                      (analyze)
+  --tablebase <file>  probe an endgame tablebase
+  --stats             collect search statistics (solve)
+  --progress          show a live status line while solving (solve)
+  --progress-log <f>  append a CSV line of search progress to a file every interval (solve)
+  --trace <file>      write the searched tree as Graphviz DOT, or as JSON if the file name
+                      ends in .json (solve with the naive or caching solver)
+  --trace-depth <n>   plies below the root to record (solve, default 4)
+  --trace-nodes <n>   most nodes to record (solve, default 10000)
+  --cache-file <file> load the caching solver's cache from a file if it exists, and save it
+                      there after the search (solve)
+  --checkpoint <file> write the search's progress at the root to a file, and resume from it
+                      if it exists (solve)
+  --checkpoint-interval <secs>
+                      how often to save the cache with the checkpoint (solve, default 600)
+  --checkpoint-cache  save the caching solver's cache with the checkpoint, and resume with it
+                      (solve)
+  --from-ply <n>      skip the game's first n moves (analyze --game)
+  --count <n>         number of positions (generate, default 10), or of random positions to
+                      search (puzzles, default 100)
+  --seed <n>          random seed (generate, puzzles and tournament, default 0)
+  --output <file>     write the suite or the puzzles to a file instead of stdout (generate
+                      and puzzles)
+  --min-win-length <n>
+                      fewest moves the winner needs, the winning one included (puzzles, default 1)
+  --max-puzzles <n>   stop after this many puzzles (puzzles, default 10)
+  --json              print JSON instead of text
+  --max-solves <n>    requests solved at once across all clients; requests beyond it are
+                      rejected (serve, default 4)
+  --engines <list>    comma-separated engines: exact[:ms], depth:<n>, mcts:<iterations>,
+                      mcts:<ms>ms or random (tournament)
+  --openings <n>      random openings, each played twice with colors swapped (tournament, default 10)
+  --opening-moves <n> random moves in each opening (tournament, default 4)
+  --book <file>       openings from a file, one position per line, instead of random ones (tournament)
+  --workers <n>       serve processes to start as workers (distribute, default the number of CPUs)
+  --connect <paths>   comma-separated sockets of serve --unix workers to use as well (distribute)
+  --split-ply <n>     plies below the position to split the search at (distribute, default 4)
+
+exit codes: 0 success, 1 verification failed, 2 usage error, 3 bad input or I/O error";
+
+const DEFAULT_SOLVER: &str = "caching";
+const SOLVER_OPTIONS: [&str; 4] = ["--solver", "--time", "--nodes", "--tablebase"];
+
+enum CliError {
+    Failed(String),
+    Usage(String),
+    Input(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Failed(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Input(_) => 3,
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Input(err.to_string())
+    }
+}
+
+struct Args {
+    positional: Vec<String>,
+    values: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    fn parse(args: &[String], value_options: &[&str], flag_options: &[&str]) -> Result<Self, CliError> {
+        let mut parsed = Args {
+            positional: vec![],
+            values: HashMap::new(),
+            flags: HashSet::new(),
+        };
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if value_options.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| CliError::Usage(format!("{arg} needs a value")))?;
+                parsed.values.insert(arg.clone(), value.clone());
+            } else if flag_options.contains(&arg.as_str()) {
+                parsed.flags.insert(arg.clone());
+            } else if arg.starts_with("--") {
+                return Err(CliError::Usage(format!("unknown option {arg}")));
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        self.values
+            .get(name)
+            .map(|value| value.parse().map_err(|_| CliError::Usage(format!("invalid value \"{value}\" for {name}"))))
+            .transpose()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    fn single_positional(&self, what: &str) -> Result<&str, CliError> {
+        match self.positional.as_slice() {
+            [value] => Ok(value),
+            [] => Err(CliError::Usage(format!("missing {what}"))),
+            _ => Err(CliError::Usage(format!("expected a single {what}"))),
+        }
+    }
+}
+
+fn options<'a>(extra: &[&'a str]) -> Vec<&'a str> {
+    SOLVER_OPTIONS.iter().chain(extra).copied().collect()
+}
+
+fn solver_config(args: &Args) -> Result<SolverConfig, CliError> {
+    let mut budget = SearchBudget::unlimited();
+
+    if let Some(secs) = args.value::<f64>("--time")? {
+        if !secs.is_finite() || secs <= 0.0 {
+            return Err(CliError::Usage("--time must be a positive number of seconds".to_string()));
+        }
+
+        budget = budget.with_time_limit(Duration::from_secs_f64(secs));
+    }
+
+    if let Some(max_nodes) = args.value("--nodes")? {
+        budget = budget.with_max_nodes(max_nodes);
+    }
+
+    let mut config = SolverConfig::default().with_budget(budget).with_stats(args.flag("--stats"));
+
+    if let Some(path) = args.values.get("--tablebase") {
+        config = config.with_tablebase(Arc::new(Tablebase::load::<StateBitboard, _>(path)?));
+    }
+
+    Ok(config)
+}
+
+fn solver_name(args: &Args) -> &str {
+    args.values.get("--solver").map_or(DEFAULT_SOLVER, String::as_str)
+}
+
+fn create_solver(args: &Args) -> Result<Box<dyn Solver<StateBitboard>>, CliError> {
+    create_solver_with_config(args, solver_config(args)?)
+}
+
+fn create_solver_with_config(args: &Args, config: SolverConfig) -> Result<Box<dyn Solver<StateBitboard>>, CliError> {
+    let name = solver_name(args);
+
+    SolverRegistry::with_default_solvers()
+        .create(name, config)
+        .ok_or_else(|| CliError::Usage(format!("unknown solver \"{name}\"")))
+}
+
+// solvers that keep their cache between the many related searches of one command
+fn create_session_solver(args: &Args) -> Result<Box<dyn Solver<StateBitboard>>, CliError> {
+    match solver_name(args) {
+        "caching" => Ok(Box::new(CachingSession::new(solver_config(args)?))),
+        "threads" => Ok(Box::new(ThreadsSession::new(solver_config(args)?))),
+        _ => create_solver(args),
+    }
+}
+
+fn file_error(path: &str, err: io::Error) -> CliError {
+    CliError::Input(format!("{path}: {err}"))
+}
+
+fn position(text: &str) -> Result<StateBitboard, CliError> {
+    // an empty move string would otherwise be the start position, which takes far too long to solve
+    if text.trim().is_empty() {
+        return Err(CliError::Input("empty position".to_string()));
+    }
+
+    parse_position(text).ok_or_else(|| CliError::Input(format!("invalid position \"{text}\"")))
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string(value).expect("reports are always serializable"));
+}
+
+fn outcome_name(eval: i32) -> &'static str {
+    match Outcome::from_eval(eval) {
+        Outcome::Win => "win",
+        Outcome::Draw => "draw",
+        Outcome::Loss => "loss",
+    }
+}
+
+#[derive(Serialize)]
+struct DistributeReport {
+    position: String,
+    score: i32,
+    outcome: &'static str,
+    workers: usize,
+    split_ply: usize,
+    subproblems: usize,
+    solved: usize,
+    states_evaluated: usize,
+    wall_time_secs: f64,
+}
+
+#[derive(Serialize)]
+struct SolveReport {
+    position: String,
+    solver: String,
+    score: i32,
+    exact: bool,
+    outcome: Option<&'static str>,
+    states_evaluated: usize,
+    wall_time_secs: f64,
+    stats: Option<SearchStats>,
+}
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// shows a status line on stderr and logs every report as CSV
+struct ProgressDisplay {
+    status_line: bool,
+    log: Option<Mutex<File>>,
+}
+
+impl ProgressDisplay {
+    fn status(progress: &SearchProgress) -> String {
+        let root_move = progress.root_move.map_or("-".to_string(), |col| (col + 1).to_string());
+        let cache = progress.cache_entries.map_or(String::new(), |entries| format!("  cache {entries}"));
+
+        format!(
+            "{:.1}s  {} nodes  {:.0} nodes/s  move {root_move}  window [{}, {}]{cache}",
+            progress.elapsed.as_secs_f64(),
+            progress.nodes,
+            progress.nodes_per_second,
+            progress.window.0,
+            progress.window.1,
+        )
+    }
+
+    fn finish(&self) {
+        if self.status_line && io::stderr().is_terminal() {
+            // clears the status line
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+impl ProgressObserver for ProgressDisplay {
+    fn on_progress(&self, progress: &SearchProgress) {
+        if self.status_line {
+            // a terminal gets one line redrawn in place, anything else a line per report
+            if io::stderr().is_terminal() {
+                eprint!("\r\x1b[K{}", Self::status(progress));
+            } else {
+                eprintln!("{}", Self::status(progress));
+            }
+        }
+
+        if let Some(log) = &self.log {
+            let line = format!(
+                "{:.3},{},{:.0},{},{},{},{}",
+                progress.elapsed.as_secs_f64(),
+                progress.nodes,
+                progress.nodes_per_second,
+                progress.root_move.map_or(String::new(), |col| (col + 1).to_string()),
+                progress.window.0,
+                progress.window.1,
+                progress.cache_entries.map_or(String::new(), |entries| entries.to_string()),
+            );
+
+            // a failed write must not stop the search
+            writeln!(log.lock().unwrap(), "{line}").ok();
+        }
+    }
+}
+
+fn load_cache(path: &str) -> Result<StateCache<StateBitboard>, CliError> {
+    if !Path::new(path).exists() {
+        return Ok(StateCache::new());
+    }
+
+    let cache = StateCache::load(path).map_err(|err| file_error(path, err))?;
+    eprintln!("loaded {} cache entries from {path}", cache.len());
+    Ok(cache)
+}
+
+// resumes from the checkpoint when the file exists
+fn checkpoint_config(args: &Args, state: &StateBitboard, path: &str) -> Result<CheckpointConfig, CliError> {
+    let mut checkpoint = CheckpointConfig::new(path).with_cache(args.flag("--checkpoint-cache"));
+
+    if let Some(secs) = args.value::<f64>("--checkpoint-interval")? {
+        if !secs.is_finite() || secs <= 0.0 {
+            return Err(CliError::Usage("--checkpoint-interval must be a positive number of seconds".to_string()));
+        }
+
+        checkpoint = checkpoint.with_interval(Duration::from_secs_f64(secs));
+    }
+
+    if checkpoint.save_cache && solver_name(args) != "caching" {
+        return Err(CliError::Usage("--checkpoint-cache needs the caching solver".to_string()));
+    }
+
+    if checkpoint.save_cache && args.values.contains_key("--cache-file") {
+        return Err(CliError::Usage("--checkpoint-cache and --cache-file cannot be used together".to_string()));
+    }
+
+    if Path::new(path).exists() {
+        let resume = RootCheckpoint::load(path).map_err(|err| file_error(path, err))?;
+
+        if !resume.is_for(state) {
+            return Err(CliError::Input(format!("{path}: a checkpoint of another position")));
+        }
+
+        eprintln!("resuming from {path}, {}/{} root moves finished", resume.finished.len(), state.next_states().len());
+        checkpoint = checkpoint.resuming(resume);
     }
 
+    Ok(checkpoint)
+}
+
+// the cache only holds proven bounds, so one saved by a search that ran out of budget is still valid
+fn solve_with_cache_file(
+    args: &Args,
+    state: StateBitboard,
+    config: &SolverConfig,
+    path: &str,
+) -> Result<(EvaluatePositionReturn, Duration), CliError> {
+    if solver_name(args) != "caching" {
+        return Err(CliError::Usage("--cache-file needs the caching solver".to_string()));
+    }
+
+    let mut cache = load_cache(path)?;
+    let start = Instant::now();
+    let ret = evaluate_position_with_cache(state, &mut cache, config);
+    let wall_time = start.elapsed();
+
+    cache.save(path).map_err(|err| file_error(path, err))?;
+    eprintln!("saved {} cache entries to {path}", cache.len());
+
+    Ok((ret, wall_time))
+}
+
+fn solve(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(
+        args,
+        &options(&["--progress-log", "--trace", "--trace-depth", "--trace-nodes", "--cache-file", "--checkpoint", "--checkpoint-interval"]),
+        &["--stats", "--json", "--progress", "--checkpoint-cache"],
+    )?;
+    let state = position(args.single_positional("position")?)?;
+
+    if state.is_win() || state.board_full() {
+        return Err(CliError::Input("the game is already over".to_string()));
+    }
+
+    let log = match args.values.get("--progress-log") {
+        Some(path) => {
+            let mut file = OpenOptions::new().append(true).create(true).open(path).map_err(|err| file_error(path, err))?;
+
+            // the header goes only at the top of a new log
+            if file.metadata().map_err(|err| file_error(path, err))?.len() == 0 {
+                writeln!(file, "elapsed_secs,nodes,nodes_per_second,root_move,alpha,beta,cache_entries").map_err(|err| file_error(path, err))?;
+            }
+
+            Some(Mutex::new(file))
+        },
+        None => None,
+    };
+
+    let mut config = solver_config(&args)?;
+    let mut display = None;
+
+    if args.flag("--progress") || log.is_some() {
+        let progress = Arc::new(ProgressDisplay { status_line: args.flag("--progress"), log });
+        config = config.with_progress(progress.clone(), PROGRESS_INTERVAL);
+        display = Some(progress);
+    }
+
+    let trace_path = args.values.get("--trace");
+
+    if trace_path.is_some() {
+        if !matches!(solver_name(&args), "naive" | "caching") {
+            return Err(CliError::Usage("--trace needs the naive or caching solver".to_string()));
+        }
+
+        config = config.with_trace(TraceConfig {
+            max_depth: args.value("--trace-depth")?.unwrap_or(DEFAULT_TRACE_MAX_DEPTH),
+            max_nodes: args.value("--trace-nodes")?.unwrap_or(DEFAULT_TRACE_MAX_NODES),
+        });
+    }
+
+    let checkpoint_path = args.values.get("--checkpoint");
+
+    if let Some(path) = checkpoint_path {
+        config = config.with_checkpoint(checkpoint_config(&args, &state, path)?);
+    }
+
+    let resumed_cache_path = config
+        .checkpoint
+        .as_ref()
+        .filter(|checkpoint| checkpoint.save_cache && checkpoint.resume.is_some())
+        .map(|checkpoint| checkpoint.cache_path().to_string_lossy().into_owned());
+
+    let (mut ret, wall_time) = match (args.values.get("--cache-file"), resumed_cache_path) {
+        (Some(path), _) => solve_with_cache_file(&args, state.clone(), &config, path)?,
+        (None, Some(cache_path)) => {
+            let mut cache = load_cache(&cache_path)?;
+            let start = Instant::now();
+            (evaluate_position_with_cache(state.clone(), &mut cache, &config), start.elapsed())
+        },
+        (None, None) => {
+            let solver = create_solver_with_config(&args, config)?;
+            let start = Instant::now();
+            (solver.evaluate(state.clone()), start.elapsed())
+        },
+    };
+
+    if let Some(display) = &display {
+        display.finish();
+    }
+
+    if let (Some(path), Some(tree)) = (trace_path, &ret.trace) {
+        let contents = if path.ends_with(".json") { tree.to_json() } else { tree.to_dot() };
+        fs::write(path, contents).map_err(|err| file_error(path, err))?;
+
+        let truncated = if tree.truncated { ", stopped at the node cap" } else { "" };
+        eprintln!("wrote {} traced nodes to {path}{truncated}", tree.nodes.len());
+    }
+
+    let checkpoint_error = ret.checkpoint_error.take();
+
+    if let Some(path) = checkpoint_path
+        && checkpoint_error.is_none()
+        && !ret.is_exact()
+    {
+        eprintln!("wrote a checkpoint to {path}; run the same command to resume");
+    }
+
+    let report = SolveReport {
+        position: format_grid(&state),
+        solver: solver_name(&args).to_string(),
+        score: ret.eval,
+        exact: ret.is_exact(),
+        outcome: ret.is_exact().then(|| outcome_name(ret.eval)),
+        states_evaluated: ret.states_evaluated,
+        wall_time_secs: wall_time.as_secs_f64(),
+        stats: ret.stats,
+    };
+
+    if args.flag("--json") {
+        print_json(&report);
+    } else {
+        match report.outcome {
+            Some(outcome) => println!("score:            {:+} ({outcome})", report.score),
+            None => println!("score:            at least {:+} (search stopped early)", report.score),
+        }
+
+        println!("states evaluated: {}", report.states_evaluated);
+        println!("wall time:        {:.3}s", report.wall_time_secs);
+
+        if let Some(stats) = &report.stats {
+            print!("{stats}");
+        }
+    }
+
+    // the result stands, but a later run could not resume from the checkpoint
+    match (checkpoint_path, checkpoint_error) {
+        (Some(path), Some(err)) => Err(file_error(path, err)),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize)]
+struct ColumnReport {
+    column: usize,
+    score: i32,
+    // when false, the score is an upper bound
+    exact: bool,
+}
+
+#[derive(Serialize)]
+struct AnalyzeReport {
+    position: String,
+    columns: Vec<ColumnReport>,
+    best_move: Option<usize>,
+}
+
+fn analyze(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &options(&["--game", "--from-ply"]), &["--json"])?;
+    let solver = create_session_solver(&args)?;
+
+    if let Some(path) = args.values.get("--game") {
+        let record: GameRecord = fs::read_to_string(path)
+            .map_err(|err| file_error(path, err))?
+            .parse()
+            .map_err(|err| CliError::Input(format!("{path}: {err}")))?;
+
+        let analysis = analyze_game(&record, solver.as_ref(), args.value("--from-ply")?.unwrap_or(0))
+            .map_err(|err| CliError::Input(format!("{path}: {err}")))?;
+
+        if args.flag("--json") {
+            println!("{}", analysis.to_json());
+        } else {
+            print!("{analysis}");
+        }
+
+        return Ok(());
+    }
+
+    let state = position(args.single_positional("position")?)?;
+
+    if state.is_win() || state.board_full() {
+        return Err(CliError::Input("the game is already over".to_string()));
+    }
+
+    let scores = solver.column_scores(&state);
+
+    let columns = (0..COLS)
+        .filter_map(|col| {
+            Some(ColumnReport {
+                column: col + 1,
+                score: scores.scores[col]?,
+                exact: scores.exact[col],
+            })
+        })
+        .collect();
+
+    let report = AnalyzeReport {
+        position: format_grid(&state),
+        columns,
+        best_move: scores.best_column().map(|col| col + 1),
+    };
+
+    if args.flag("--json") {
+        print_json(&report);
+        return Ok(());
+    }
+
+    print!("{state}");
+    println!("column  score");
+
+    for column in &report.columns {
+        if column.exact {
+            println!("{:>6}  {:+} ({})", column.column, column.score, outcome_name(column.score));
+        } else {
+            println!("{:>6}  at most {:+} (search stopped early)", column.column, column.score);
+        }
+    }
+
+    if let Some(best_move) = report.best_move {
+        println!("best move: {best_move}");
+    }
+
+    Ok(())
+}
+
+fn generate(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &options(&["--chain", "--moves", "--count", "--seed", "--output"]), &[])?;
+
+    if let Some(range) = args.values.get("--chain") {
+        let invalid = || CliError::Usage(format!("--chain expects <from>..<to>, got \"{range}\""));
+        let (from, to) = range.split_once("..").ok_or_else(invalid)?;
+        let (from, to): (usize, usize) = (from.parse().map_err(|_| invalid())?, to.parse().map_err(|_| invalid())?);
+
+        for depth in from..=to {
+            generate_state_file(depth)?;
+        }
+
+        return Ok(());
+    }
+
+    let moves_made: usize = args.value("--moves")?.ok_or_else(|| CliError::Usage("generate needs --moves or --chain".to_string()))?;
+    let count = args.value("--count")?.unwrap_or(10);
+    let seed = args.value("--seed")?.unwrap_or(0);
+    let solver = create_solver(&args)?;
+
+    if moves_made >= BOARD_SIZE {
+        return Err(CliError::Usage(format!("--moves must be below {BOARD_SIZE}")));
+    }
+
+    let states: Vec<StateBitboard> = random_positions(seed, count, moves_made).collect();
+
+    if states.len() < count {
+        return Err(CliError::Input(format!("random games kept ending before {moves_made} moves; found {} of {count} positions", states.len())));
+    }
+
+    let batch = solve_batch(states.clone(), solver.as_ref(), &solver.config().budget, default_thread_count());
+
+    let mut entries = vec![];
+
+    for (state, ret) in states.into_iter().zip(&batch.results) {
+        if ret.is_exact() {
+            entries.push((state, ret.eval));
+        }
+    }
+
+    match args.values.get("--output") {
+        Some(path) => File::create(path).and_then(|mut file| write_suite(&mut file, &entries)).map_err(|err| file_error(path, err))?,
+        None => write_suite(&mut io::stdout().lock(), &entries)?,
+    }
+
+    eprintln!("wrote {} of {} positions, {} ran out of budget", entries.len(), batch.results.len(), batch.results.len() - entries.len());
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Mismatch {
+    index: usize,
+    position: String,
+    expected: i32,
+    actual: i32,
+    exact: bool,
+}
+
+#[derive(Serialize)]
+struct VerifyReport {
+    positions: usize,
+    checked: usize,
+    mismatches: Vec<Mismatch>,
+    states_evaluated: usize,
+    wall_time_secs: f64,
+}
+
+fn verify(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &options(&[]), &["--json"])?;
+    let path = args.single_positional("suite")?;
+    let solver = create_solver(&args)?;
+
+    let suite: Vec<(StateBitboard, Option<i32>)> = read_suite(path).map_err(|err| file_error(path, err))?;
+    let scored: Vec<(usize, StateBitboard, i32)> = suite
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (state, score))| score.map(|score| (index + 1, state.clone(), score)))
+        .collect();
+
+    let states = scored.iter().map(|(_, state, _)| state.clone()).collect();
+    let batch = solve_batch(states, solver.as_ref(), &solver.config().budget, default_thread_count());
+
+    let mismatches: Vec<Mismatch> = scored
+        .iter()
+        .zip(&batch.results)
+        .filter(|((_, _, expected), ret)| !ret.is_exact() || ret.eval != *expected)
+        .map(|((index, state, expected), ret)| Mismatch {
+            index: *index,
+            position: format_grid(state),
+            expected: *expected,
+            actual: ret.eval,
+            exact: ret.is_exact(),
+        })
+        .collect();
+
+    let report = VerifyReport {
+        positions: suite.len(),
+        checked: scored.len(),
+        mismatches,
+        states_evaluated: batch.stats.states_evaluated,
+        wall_time_secs: batch.stats.wall_time.as_secs_f64(),
+    };
+
+    if args.flag("--json") {
+        print_json(&report);
+    } else {
+        for mismatch in &report.mismatches {
+            let bound = if mismatch.exact { "" } else { "at least " };
+            println!("position {}: expected {:+}, got {bound}{:+}", mismatch.index, mismatch.expected, mismatch.actual);
+        }
+
+        println!("checked {} of {} positions, {} mismatched", report.checked, report.positions, report.mismatches.len());
+    }
+
+    if report.mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::Failed(format!("{} of {} positions did not match", report.mismatches.len(), report.checked)))
+    }
+}
+
+fn puzzles(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(
+        args,
+        &options(&["--moves", "--suite", "--count", "--seed", "--min-win-length", "--max-puzzles", "--output"]),
+        &[],
+    )?;
+
+    if let Some(arg) = args.positional.first() {
+        return Err(CliError::Usage(format!("unexpected argument \"{arg}\"")));
+    }
+
+    let candidates: Vec<StateBitboard> = match (args.values.get("--suite"), args.value::<usize>("--moves")?) {
+        (Some(path), None) => read_suite(path).map_err(|err| file_error(path, err))?.into_iter().map(|(state, _)| state).collect(),
+        (None, Some(moves_made)) => {
+            if moves_made >= BOARD_SIZE {
+                return Err(CliError::Usage(format!("--moves must be below {BOARD_SIZE}")));
+            }
+
+            random_positions(args.value("--seed")?.unwrap_or(0), args.value("--count")?.unwrap_or(100), moves_made).collect()
+        },
+        _ => return Err(CliError::Usage("puzzles needs either --moves or --suite".to_string())),
+    };
+
+    // the candidates often share positions deeper in their trees, so one cache serves them all
+    let solver = create_session_solver(&args)?;
+    let min_win_length = args.value("--min-win-length")?.unwrap_or(1);
+    let puzzles = search_puzzles(candidates.iter().cloned(), solver.as_ref(), min_win_length, args.value("--max-puzzles")?.unwrap_or(10));
+
+    match args.values.get("--output") {
+        Some(path) => write_puzzle_file(path, &puzzles).map_err(|err| file_error(path, err))?,
+        None => write_puzzles(&mut io::stdout().lock(), &puzzles)?,
+    }
+
+    eprintln!("found {} puzzles in {} positions", puzzles.len(), candidates.len());
+    Ok(())
+}
+
+fn engine(args: &[String]) -> Result<(), CliError> {
+    if let Some(arg) = Args::parse(args, &[], &[])?.positional.first() {
+        return Err(CliError::Usage(format!("unexpected argument \"{arg}\"")));
+    }
+
+    Engine::<StateBitboard>::new().run(io::stdin().lock(), io::stdout())?;
+    Ok(())
+}
+
+fn serve(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &["--listen", "--unix", "--max-solves"], &[])?;
+
+    if let Some(arg) = args.positional.first() {
+        return Err(CliError::Usage(format!("unexpected argument \"{arg}\"")));
+    }
+
+    let server = Server::<StateBitboard>::new(args.value("--max-solves")?.unwrap_or(DEFAULT_MAX_CONCURRENT_SOLVES));
+
+    if let Some(addr) = args.values.get("--listen") {
+        let listener = TcpListener::bind(addr).map_err(|err| CliError::Input(format!("{addr}: {err}")))?;
+        eprintln!("listening on {}", listener.local_addr()?);
+        server.serve_tcp(listener);
+        return Ok(());
+    }
+
+    if let Some(path) = args.values.get("--unix") {
+        return serve_unix(&server, path);
+    }
+
+    server.service().serve_lines(io::stdin().lock(), io::stdout().lock())?;
     Ok(())
 }
+
+#[cfg(unix)]
+fn serve_unix(server: &Arc<Server<StateBitboard>>, path: &str) -> Result<(), CliError> {
+    let listener = UnixListener::bind(path).map_err(|err| file_error(path, err))?;
+    eprintln!("listening on {path}");
+    server.serve_unix(listener);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_unix(_server: &Arc<Server<StateBitboard>>, _path: &str) -> Result<(), CliError> {
+    Err(CliError::Usage("Unix sockets are not supported on this platform".to_string()))
+}
+
+fn distribute(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &["--solver", "--workers", "--connect", "--split-ply"], &["--json"])?;
+    let state = position(args.single_positional("position")?)?;
+
+    if state.is_win() || state.board_full() {
+        return Err(CliError::Input("the game is already over".to_string()));
+    }
+
+    let solver = solver_name(&args);
+
+    if !matches!(solver, "naive" | "caching" | "threads") {
+        return Err(CliError::Usage(format!("unknown solver \"{solver}\"")));
+    }
+
+    let sockets: Vec<&str> = args.values.get("--connect").map_or(vec![], |list| list.split(',').map(str::trim).collect());
+    let worker_count = match args.value("--workers")? {
+        Some(count) => count,
+        None if sockets.is_empty() => default_thread_count(),
+        None => 0,
+    };
+
+    if worker_count + sockets.len() == 0 {
+        return Err(CliError::Usage("distribute needs at least one worker".to_string()));
+    }
+
+    let mut workers = vec![];
+    let started = (0..worker_count).map(spawn_worker).chain(sockets.into_iter().map(connect_worker));
+
+    for worker in started {
+        match worker {
+            Ok(worker) => workers.push(worker),
+            Err(err) => {
+                // the workers started so far would otherwise keep running
+                workers.into_iter().for_each(WorkerConnection::close);
+                return Err(err);
+            },
+        }
+    }
+
+    let workers_len = workers.len();
+    let split_ply = args.value("--split-ply")?.unwrap_or(DEFAULT_SPLIT_PLY);
+
+    let start = Instant::now();
+    let ret = solve_distributed(&state, split_ply, Some(solver), workers)?;
+
+    let report = DistributeReport {
+        position: format_grid(&state),
+        score: ret.eval,
+        outcome: outcome_name(ret.eval),
+        workers: workers_len,
+        split_ply,
+        subproblems: ret.subproblems,
+        solved: ret.solved,
+        states_evaluated: ret.states_evaluated,
+        wall_time_secs: start.elapsed().as_secs_f64(),
+    };
+
+    if args.flag("--json") {
+        print_json(&report);
+        return Ok(());
+    }
+
+    println!("score:            {:+} ({})", report.score, report.outcome);
+    println!("subproblems:      {} at ply {}, {} solved", report.subproblems, report.split_ply, report.solved);
+    println!("workers:          {}", report.workers);
+    println!("states evaluated: {}", report.states_evaluated);
+    println!("wall time:        {:.3}s", report.wall_time_secs);
+    Ok(())
+}
+
+// on Unix the workers listen on sockets of their own, so that subproblems no longer needed are cancelled
+#[cfg(unix)]
+fn spawn_worker(index: usize) -> Result<WorkerConnection, CliError> {
+    let mut command = Command::new(env::current_exe()?);
+    command.arg("serve");
+
+    let path = env::temp_dir().join(format!("software_testing_project-{}-{index}.sock", std::process::id()));
+    Ok(WorkerConnection::spawn_unix(command, path)?)
+}
+
+#[cfg(not(unix))]
+fn spawn_worker(_index: usize) -> Result<WorkerConnection, CliError> {
+    let mut command = Command::new(env::current_exe()?);
+    command.arg("serve");
+    Ok(WorkerConnection::spawn(command)?)
+}
+
+#[cfg(unix)]
+fn connect_worker(path: &str) -> Result<WorkerConnection, CliError> {
+    WorkerConnection::connect_unix(path).map_err(|err| file_error(path, err))
+}
+
+#[cfg(not(unix))]
+fn connect_worker(_path: &str) -> Result<WorkerConnection, CliError> {
+    Err(CliError::Usage("Unix sockets are not supported on this platform".to_string()))
+}
+
+// the result is from X's side, as in game records
+fn game_summary(tournament: &Tournament<StateBitboard>, game: &TournamentGame) -> String {
+    let (x, o) = if game.first_plays_x { (game.first, game.second) } else { (game.second, game.first) };
+
+    let result = match game.winner {
+        Some(winner) if winner == x => "1-0",
+        Some(_) => "0-1",
+        None => "1/2-1/2",
+    };
+
+    let forfeit = if game.forfeit { " by forfeit" } else { "" };
+
+    format!(
+        "{} (X) vs {} (O), opening {}: {result}{forfeit} ({})",
+        tournament.entrant_name(x),
+        tournament.entrant_name(o),
+        game.opening + 1,
+        format_moves(&game.moves),
+    )
+}
+
+fn tournament(args: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(args, &["--engines", "--openings", "--opening-moves", "--book", "--seed"], &["--json"])?;
+
+    if let Some(arg) = args.positional.first() {
+        return Err(CliError::Usage(format!("unexpected argument \"{arg}\"")));
+    }
+
+    let engines = args.values.get("--engines").ok_or_else(|| CliError::Usage("tournament needs --engines".to_string()))?;
+    let specs: Vec<String> = engines.split(',').map(|spec| spec.trim().to_string()).collect();
+
+    if specs.len() < 2 {
+        return Err(CliError::Usage("a tournament needs at least two engines".to_string()));
+    }
+
+    if let Some(spec) = specs.iter().find(|spec| create_player::<StateBitboard>(spec, 0).is_none()) {
+        return Err(CliError::Usage(format!("unknown engine \"{spec}\"")));
+    }
+
+    let seed = args.value("--seed")?.unwrap_or(0);
+
+    let openings: Vec<StateBitboard> = match args.values.get("--book") {
+        Some(path) => read_book(path).map_err(|err| file_error(path, err))?,
+        None => {
+            let opening_moves = args.value("--opening-moves")?.unwrap_or(4);
+
+            if opening_moves >= BOARD_SIZE {
+                return Err(CliError::Usage(format!("--opening-moves must be below {BOARD_SIZE}")));
+            }
+
+            random_positions(seed, args.value("--openings")?.unwrap_or(10), opening_moves).collect()
+        },
+    };
+
+    if openings.is_empty() {
+        return Err(CliError::Input("no openings to play".to_string()));
+    }
+
+    let mut tournament = Tournament::new(openings, seed);
+
+    for spec in specs {
+        let name = spec.clone();
+        tournament = tournament.with_entrant(name, move |seed| create_player(&spec, seed).unwrap());
+    }
+
+    let game_count = tournament.game_count();
+    let mut games_played = 0;
+
+    let report = tournament.run(|game| {
+        games_played += 1;
+        eprintln!("game {games_played}/{game_count}: {}", game_summary(&tournament, game));
+    });
+
+    if args.flag("--json") {
+        print_json(&report);
+    } else {
+        print!("{report}");
+    }
+
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage("missing command".to_string()));
+    };
+
+    match command.as_str() {
+        "solve" => solve(rest),
+        "analyze" => analyze(rest),
+        "generate" => generate(rest),
+        "verify" => verify(rest),
+        "puzzles" => puzzles(rest),
+        "engine" => engine(rest),
+        "serve" => serve(rest),
+        "tournament" => tournament(rest),
+        "distribute" => distribute(rest),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        },
+        _ => Err(CliError::Usage(format!("unknown command \"{command}\""))),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            match &err {
+                CliError::Usage(message) => eprintln!("error: {message}\n\n{USAGE}"),
+                CliError::Failed(message) | CliError::Input(message) => eprintln!("error: {message}"),
+            }
+
+            ExitCode::from(err.exit_code())
+        },
+    }
+}
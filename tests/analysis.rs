@@ -0,0 +1,52 @@
+use software_testing_project::connect_four::analysis::analyze_game;
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::game_record::GameRecord;
+use software_testing_project::connect_four::move_string::parse_moves;
+use software_testing_project::connect_four::solver::{CachingSession, SolverConfig};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// ply 20 turns a draw into a loss, ply 21 keeps a win, ply 22 keeps a loss and ply 23 throws away
+// the win that only column 3 keeps
+const GAME: &str = "43523412255117766136641";
+const FIRST_PLY: usize = 19;
+
+fn record() -> GameRecord {
+    GameRecord::from_moves("first", "second", &parse_moves(GAME).unwrap())
+}
+
+#[test]
+fn giving_away_a_win_is_a_blunder() {
+    let solver = CachingSession::<StateBitboard>::new(SolverConfig::default());
+    let analysis = analyze_game(&record(), &solver, FIRST_PLY).unwrap();
+    let ply = &analysis.plies[3];
+
+    assert_eq!(ply.ply, 23);
+    assert!(ply.exact && ply.blunder);
+    assert_eq!(ply.optimal_columns, vec![2]);
+    assert!(ply.score_before > 0 && ply.score_after < 0);
+}
+
+#[test]
+fn moves_that_give_away_no_win_are_not_blunders() {
+    let solver = CachingSession::<StateBitboard>::new(SolverConfig::default());
+    let analysis = analyze_game(&record(), &solver, FIRST_PLY).unwrap();
+    let plies: Vec<_> = analysis.plies.iter().take(3).collect();
+
+    assert!(plies.iter().all(|ply| ply.exact && !ply.blunder));
+
+    // a draw turned into a loss loses score, but there was no win to give away
+    assert_eq!((plies[0].score_before, plies[0].score_after), (0, -11));
+    assert!(plies[1].score_loss() > 0 && plies[1].score_after > 0);
+    assert_eq!(analysis.blunders().count(), 1);
+}
+
+#[test]
+fn scores_that_are_only_bounds_are_not_blunders() {
+    let budget = SearchBudget::unlimited().with_max_nodes(50);
+    let solver = CachingSession::<StateBitboard>::new(SolverConfig::default().with_budget(budget));
+    let analysis = analyze_game(&record(), &solver, FIRST_PLY).unwrap();
+
+    assert!(analysis.plies.iter().any(|ply| !ply.exact));
+    assert!(analysis.plies.iter().filter(|ply| ply.blunder).all(|ply| ply.exact));
+    assert!(!analysis.plies[3].blunder);
+}
@@ -0,0 +1,65 @@
+use software_testing_project::connect_four::batch::solve_batch;
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::move_string::{parse_moves, play_moves};
+use software_testing_project::connect_four::solver::{
+    CachingSession, CachingSolver, NaiveSolver, SharedCachingSession, Solver, SolverConfig, ThreadsSession, ThreadsSolver,
+};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const POSITIONS: [&str; 4] = ["435234122551177661366", "4352341225511776613", "43523412255117766", "4352341225511776"];
+
+fn positions() -> Vec<StateBitboard> {
+    POSITIONS.iter().map(|moves| play_moves(&parse_moves(moves).unwrap()).unwrap()).collect()
+}
+
+#[test]
+fn results_keep_the_order_of_the_positions() {
+    let solver = CachingSolver::new(SolverConfig::default());
+    let batch = solve_batch(positions(), &solver, &SearchBudget::unlimited(), 3);
+
+    for (state, ret) in positions().into_iter().zip(&batch.results) {
+        assert!(ret.is_exact());
+        assert_eq!(ret.eval, solver.evaluate(state).eval);
+    }
+
+    assert_eq!(batch.stats.exact_results, POSITIONS.len());
+}
+
+#[test]
+fn the_budget_applies_to_each_position() {
+    let budget = SearchBudget::unlimited().with_max_nodes(100);
+    let batch = solve_batch(positions(), &CachingSolver::new(SolverConfig::default()), &budget, 2);
+
+    assert_eq!(batch.stats.positions, POSITIONS.len());
+    assert!(batch.results.iter().all(|ret| ret.states_evaluated <= 100));
+    assert!(batch.results.iter().any(|ret| !ret.is_exact()));
+}
+
+#[test]
+fn the_threads_solver_solves_a_batch() {
+    let batch = solve_batch(positions(), &ThreadsSession::new(SolverConfig::default()), &SearchBudget::unlimited(), 4);
+    assert_eq!(batch.stats.exact_results, POSITIONS.len());
+}
+
+#[test]
+fn a_caching_session_solves_a_batch() {
+    let batch = solve_batch(positions(), &CachingSession::new(SolverConfig::default()), &SearchBudget::unlimited(), 4);
+    assert_eq!(batch.stats.exact_results, POSITIONS.len());
+}
+
+#[test]
+fn only_solvers_without_threads_or_a_locked_cache_are_parallel_safe() {
+    let config = SolverConfig::default();
+    let solvers: [(Box<dyn Solver<StateBitboard>>, bool); 6] = [
+        (Box::new(NaiveSolver::new(config.clone())), true),
+        (Box::new(CachingSolver::new(config.clone())), true),
+        (Box::new(SharedCachingSession::new(config.clone())), true),
+        (Box::new(ThreadsSolver::new(config.clone())), false),
+        (Box::new(ThreadsSession::new(config.clone())), false),
+        (Box::new(CachingSession::new(config)), false),
+    ];
+
+    for (solver, parallel_safe) in solvers {
+        assert_eq!(solver.parallel_safe(), parallel_safe, "{}", solver.name());
+    }
+}
@@ -0,0 +1,101 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use software_testing_project::connect_four::budget::{CancelToken, SearchBudget};
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{Solver, SolverConfig, SolverRegistry};
+use software_testing_project::connect_four::solver_util::ScoreBound;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// far too early in the game for any solver to finish within these budgets
+const EARLY_POSITION: &str = "4444555";
+const MAX_NODES: usize = 2000;
+
+fn early_state() -> StateBitboard {
+    parse_position(EARLY_POSITION).unwrap()
+}
+
+fn solvers(budget: SearchBudget) -> Vec<Box<dyn Solver<StateBitboard>>> {
+    SolverRegistry::with_default_solvers().create_all(SolverConfig::default().with_budget(budget))
+}
+
+#[test]
+fn a_node_limit_stops_every_solver_with_a_bound() {
+    for solver in solvers(SearchBudget::unlimited().with_max_nodes(MAX_NODES)) {
+        let ret = solver.evaluate(early_state());
+
+        assert_eq!(ret.bound, ScoreBound::Lower, "{}", solver.name());
+        assert!(ret.states_evaluated > 0, "{}", solver.name());
+    }
+}
+
+#[test]
+fn a_node_limit_holds_for_the_single_threaded_solvers() {
+    for solver in solvers(SearchBudget::unlimited().with_max_nodes(MAX_NODES)) {
+        if solver.name() != "threads" {
+            assert!(solver.evaluate(early_state()).states_evaluated <= MAX_NODES, "{}", solver.name());
+        }
+    }
+}
+
+#[test]
+fn a_cancelled_token_stops_every_solver_at_once() {
+    let cancel_token = CancelToken::new();
+    cancel_token.cancel();
+
+    for solver in solvers(SearchBudget::unlimited().with_cancel_token(cancel_token.clone())) {
+        let ret = solver.evaluate(early_state());
+        assert!(!ret.is_exact(), "{}", solver.name());
+
+        // the threads solver's helpers search until its main search returns, and count their nodes too
+        if solver.name() != "threads" {
+            assert!(ret.states_evaluated <= 1, "{}", solver.name());
+        }
+    }
+}
+
+#[test]
+fn cancelling_stops_a_running_search() {
+    for name in ["caching", "threads"] {
+        let cancel_token = CancelToken::new();
+        let budget = SearchBudget::unlimited().with_cancel_token(cancel_token.clone());
+        let solver = SolverRegistry::<StateBitboard>::with_default_solvers()
+            .create(name, SolverConfig::default().with_budget(budget))
+            .unwrap();
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancel_token.cancel();
+        });
+
+        assert!(!solver.evaluate(early_state()).is_exact(), "{name}");
+        canceller.join().unwrap();
+    }
+}
+
+#[test]
+fn a_time_limit_stops_a_search() {
+    for solver in solvers(SearchBudget::unlimited().with_time_limit(Duration::from_millis(50))) {
+        let start = Instant::now();
+        let ret = solver.evaluate(early_state());
+
+        assert!(!ret.is_exact(), "{}", solver.name());
+        assert!(start.elapsed() < Duration::from_secs(5), "{}", solver.name());
+    }
+}
+
+#[test]
+fn the_columns_share_one_node_limit() {
+    let budget = SearchBudget::unlimited().with_max_nodes(MAX_NODES);
+
+    for solver in solvers(SearchBudget::unlimited()) {
+        if solver.name() == "threads" {
+            continue;
+        }
+
+        let config = solver.config().clone().with_budget(budget.clone());
+        let column_scores = solver.column_scores_with_config(&early_state(), &config);
+
+        assert!(column_scores.states_evaluated <= MAX_NODES, "{}", solver.name());
+        assert!(!column_scores.is_exact(), "{}", solver.name());
+    }
+}
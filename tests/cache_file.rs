@@ -0,0 +1,93 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use software_testing_project::connect_four::cache_strategy::StateCache;
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSession, Solver, SolverConfig};
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const LATE_POSITION: &str = "435234122551177661366";
+
+// the magic takes the first four bytes and the version the fifth
+const VERSION_OFFSET: usize = 4;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("cache-file-test-{}-{name}", std::process::id()))
+}
+
+// solves the position and saves the cache it leaves behind
+fn saved_cache(name: &str) -> PathBuf {
+    let path = temp_path(name);
+    let session = CachingSession::new(SolverConfig::default());
+
+    session.evaluate(parse_position::<StateBitboard>(LATE_POSITION).unwrap());
+    session.save_cache(&path).unwrap();
+    path
+}
+
+fn assert_rejected<S: State>(path: &Path) {
+    let err = StateCache::<S>::load(path).err().expect("the file is rejected");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn a_loaded_cache_solves_like_the_saved_one() {
+    let path = saved_cache("round-trip");
+    let state = parse_position::<StateBitboard>(LATE_POSITION).unwrap();
+
+    let fresh = CachingSession::new(SolverConfig::default()).evaluate(state.clone());
+    let loaded = StateCache::<StateBitboard>::load(&path).unwrap();
+    let loaded_len = loaded.len();
+    let session = CachingSession::with_cache(SolverConfig::default(), loaded);
+
+    assert_eq!(session.cache_len(), loaded_len);
+    assert!(loaded_len > 0);
+
+    let warm = session.evaluate(state);
+    assert_eq!((warm.eval, warm.is_exact()), (fresh.eval, fresh.is_exact()));
+    assert!(warm.states_evaluated < fresh.states_evaluated);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn files_with_another_magic_are_rejected() {
+    let path = saved_cache("magic");
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[0] ^= 0xff;
+    fs::write(&path, bytes).unwrap();
+
+    assert_rejected::<StateBitboard>(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn files_with_another_version_are_rejected() {
+    let path = saved_cache("version");
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[VERSION_OFFSET] += 1;
+    fs::write(&path, bytes).unwrap();
+
+    assert_rejected::<StateBitboard>(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn files_for_another_representation_are_rejected() {
+    let path = saved_cache("representation");
+
+    assert_rejected::<StateArray>(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn truncated_files_are_rejected() {
+    let path = saved_cache("truncated");
+    let bytes = fs::read(&path).unwrap();
+    fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+
+    assert_rejected::<StateBitboard>(&path);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::checkpoint::{CheckpointConfig, RootCheckpoint};
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSolver, Solver, SolverConfig};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// long enough for a node limit to stop it part of the way through its root moves
+const POSITION: &str = "43523412255117766";
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+#[test]
+fn an_interrupted_search_resumes_from_its_checkpoint() {
+    let path = std::env::temp_dir().join(format!("checkpoint-test-{}", std::process::id()));
+    let solver = CachingSolver::new(SolverConfig::default());
+    let expected = solver.evaluate(state(POSITION));
+
+    let interrupted_config = SolverConfig::default()
+        .with_budget(SearchBudget::unlimited().with_max_nodes(expected.states_evaluated / 2))
+        .with_checkpoint(CheckpointConfig::new(&path));
+    let interrupted = solver.evaluate_with_config(state(POSITION), &interrupted_config);
+    let checkpoint = RootCheckpoint::load(&path).unwrap();
+
+    assert!(!interrupted.is_exact());
+    assert!(!checkpoint.complete && checkpoint.is_for(&state(POSITION)));
+    assert!(!checkpoint.finished.is_empty());
+
+    let resumed_config = SolverConfig::default().with_checkpoint(CheckpointConfig::new(&path).resuming(checkpoint));
+    let resumed = solver.evaluate_with_config(state(POSITION), &resumed_config);
+    let checkpoint = RootCheckpoint::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(resumed.is_exact());
+    assert_eq!(resumed.eval, expected.eval);
+    assert!(checkpoint.complete);
+}
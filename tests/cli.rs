@@ -0,0 +1,36 @@
+use std::fs;
+use std::process::{Command, Output};
+
+const LATE_POSITION: &str = "435234122551177661366";
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_software_testing_project")).args(args).output().unwrap()
+}
+
+#[test]
+fn an_empty_position_is_bad_input() {
+    for position in ["", "  "] {
+        let output = run(&["solve", position]);
+
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("empty position"));
+    }
+}
+
+#[test]
+fn the_progress_log_is_appended_to() {
+    let path = std::env::temp_dir().join(format!("cli-test-{}-progress.csv", std::process::id()));
+    let path_arg = path.to_str().unwrap();
+
+    assert!(run(&["solve", LATE_POSITION, "--progress-log", path_arg]).status.success());
+    let first_run = fs::read_to_string(&path).unwrap();
+    assert!(first_run.starts_with("elapsed_secs,"));
+
+    assert!(run(&["solve", LATE_POSITION, "--progress-log", path_arg]).status.success());
+    let log = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // the second run keeps the first run's lines and writes no second header
+    assert!(log.starts_with(&first_run));
+    assert_eq!(log.matches("elapsed_secs,").count(), 1);
+}
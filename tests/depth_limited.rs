@@ -0,0 +1,68 @@
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::depth_limited::{evaluate_position, search_with_budget, DepthLimitedResult, PROVEN_SCORE_OFFSET};
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSolver, Solver, SolverConfig};
+use software_testing_project::connect_four::solver_util::BOARD_SIZE;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// the first player wins by playing column 1 again
+const IMMEDIATE_WIN: &str = "121212";
+// the second player has to block the first player's three in column 1
+const THREAT: &str = "12131";
+// a win and a loss late enough to be searched to the end of the game
+const LATE_POSITIONS: [&str; 2] = ["43523412255117766131", "435234122551177661366"];
+const EARLY_POSITION: &str = "4444555";
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+fn result_with_score(score: i32) -> DepthLimitedResult {
+    DepthLimitedResult { score, best_move: None, depth: 1, states_evaluated: 0 }
+}
+
+#[test]
+fn an_immediate_win_is_taken() {
+    let result = evaluate_position(state(IMMEDIATE_WIN), 4);
+
+    assert_eq!(result.best_move, Some(0));
+    assert_eq!(result.proven_eval(), Some(CachingSolver::new(SolverConfig::default()).evaluate(state(IMMEDIATE_WIN)).eval));
+}
+
+#[test]
+fn a_threat_is_blocked() {
+    assert_eq!(evaluate_position(state(THREAT), 4).best_move, Some(0));
+}
+
+#[test]
+fn searching_to_the_end_proves_the_exact_score() {
+    let solver = CachingSolver::new(SolverConfig::default());
+
+    for moves in LATE_POSITIONS {
+        let result = search_with_budget(&state(moves), BOARD_SIZE, &SearchBudget::unlimited());
+        let eval = solver.evaluate(state(moves)).eval;
+
+        assert_eq!(result.proven_eval(), Some(eval), "{moves}");
+        assert_eq!(result.score, eval.signum() * (eval.abs() + PROVEN_SCORE_OFFSET), "{moves}");
+    }
+}
+
+#[test]
+fn proven_scores_are_offset_past_heuristic_ones() {
+    assert_eq!(result_with_score(PROVEN_SCORE_OFFSET + 5).proven_eval(), Some(5));
+    assert_eq!(result_with_score(-PROVEN_SCORE_OFFSET - 3).proven_eval(), Some(-3));
+    assert_eq!(result_with_score(PROVEN_SCORE_OFFSET - 1).proven_eval(), None);
+
+    let heuristic = evaluate_position(state(EARLY_POSITION), 4);
+
+    assert_eq!(heuristic.proven_eval(), None);
+    assert!(heuristic.score.abs() < PROVEN_SCORE_OFFSET);
+}
+
+#[test]
+fn a_node_limit_keeps_the_deepest_finished_iteration() {
+    let result = search_with_budget(&state(EARLY_POSITION), BOARD_SIZE, &SearchBudget::unlimited().with_max_nodes(5000));
+
+    assert!(result.depth > 0 && result.depth < BOARD_SIZE);
+    assert!(result.best_move.is_some());
+}
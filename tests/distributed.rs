@@ -0,0 +1,48 @@
+use std::process::{self, Command};
+use software_testing_project::connect_four::cache_strategy;
+use software_testing_project::connect_four::distributed::{solve_distributed, WorkerConnection};
+use software_testing_project::connect_four::move_string::{parse_moves, play_moves};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// far enough into the game that the workers solve every subproblem quickly, even in a debug build
+const POSITIONS: [&str; 3] = ["435234122551177661366", "4352341225511776613", "43523412255117766"];
+const SPLIT_PLY: usize = 2;
+
+fn serve_command() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_software_testing_project"));
+    command.arg("serve");
+    command
+}
+
+fn position(moves: &str) -> StateBitboard {
+    play_moves(&parse_moves(moves).unwrap()).unwrap()
+}
+
+#[test]
+fn workers_on_stdin_agree_with_the_caching_solver() {
+    for moves in POSITIONS {
+        let state = position(moves);
+        let workers = vec![WorkerConnection::spawn(serve_command()).unwrap(), WorkerConnection::spawn(serve_command()).unwrap()];
+
+        let ret = solve_distributed(&state, SPLIT_PLY, None, workers).unwrap();
+        assert_eq!(ret.eval, cache_strategy::evaluate_position(state).eval, "{moves}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn workers_on_sockets_agree_with_the_caching_solver() {
+    for (index, moves) in POSITIONS.into_iter().enumerate() {
+        let state = position(moves);
+
+        let workers = (0..2)
+            .map(|worker| {
+                let path = std::env::temp_dir().join(format!("distributed-test-{}-{index}-{worker}.sock", process::id()));
+                WorkerConnection::spawn_unix(serve_command(), path).unwrap()
+            })
+            .collect();
+
+        let ret = solve_distributed(&state, SPLIT_PLY, Some("caching"), workers).unwrap();
+        assert_eq!(ret.eval, cache_strategy::evaluate_position(state).eval, "{moves}");
+    }
+}
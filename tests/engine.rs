@@ -0,0 +1,64 @@
+use std::io::Cursor;
+use software_testing_project::connect_four::engine::{Engine, ENGINE_NAME};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// the engine's output for a script of commands, one per line
+fn run_script(script: &str) -> Vec<String> {
+    let mut output = vec![];
+    Engine::<StateBitboard>::new().run(Cursor::new(script), &mut output).unwrap();
+    String::from_utf8(output).unwrap().lines().map(str::to_string).collect()
+}
+
+fn best_move(lines: &[String]) -> &str {
+    let line = lines.iter().find(|line| line.starts_with("bestmove ")).expect("the engine sends a best move");
+    line.trim_start_matches("bestmove ")
+}
+
+#[test]
+fn uci_is_answered_with_the_engine_name() {
+    assert_eq!(run_script("uci\n"), [format!("id name {ENGINE_NAME}"), "uciok".to_string()]);
+}
+
+#[test]
+fn isready_is_answered() {
+    assert_eq!(run_script("isready\n"), ["readyok"]);
+}
+
+#[test]
+fn go_with_a_node_limit_sends_a_best_move() {
+    let lines = run_script("position startpos moves 4444555\ngo nodes 2000\n");
+    let col: usize = best_move(&lines).parse().unwrap();
+
+    assert!((1..=7).contains(&col));
+    assert!(lines.iter().any(|line| line.starts_with("info depth")));
+}
+
+#[test]
+fn go_takes_an_immediate_win() {
+    let lines = run_script("position moves 121212\ngo\n");
+    assert_eq!(best_move(&lines), "1");
+}
+
+#[test]
+fn an_infinite_search_sends_its_best_move_once_stopped() {
+    let lines = run_script("position startpos\ngo infinite\nstop\nisready\n");
+    let best_move_index = lines.iter().position(|line| line.starts_with("bestmove ")).expect("the engine sends a best move");
+
+    assert_eq!(lines.last().unwrap(), "readyok");
+    assert!(best_move_index < lines.len() - 1);
+}
+
+#[test]
+fn invalid_commands_are_reported_as_info_strings() {
+    let lines = run_script("position moves 4449\ngo sometime\nhello\nisready\n");
+
+    assert_eq!(
+        lines,
+        [
+            "info string invalid position \"moves 4449\"",
+            "info string invalid go options \"sometime\"",
+            "info string unknown command \"hello\"",
+            "readyok",
+        ]
+    );
+}
@@ -0,0 +1,143 @@
+use std::ffi::CString;
+use std::ptr;
+use software_testing_project::connect_four::ffi::*;
+use software_testing_project::connect_four::naive;
+use software_testing_project::connect_four::move_string::{format_grid, parse_position};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const UNLIMITED: C4Budget = C4Budget { time_ms: 0, max_nodes: 0 };
+
+// late enough in the game that every test solves it quickly, even in a debug build
+const POSITION: &str = "435234122551177661366";
+
+fn set_position(session: *mut C4Session, position: &str) -> i32 {
+    let position = CString::new(position).unwrap();
+    unsafe { c4_session_set_position(session, position.as_ptr()) }
+}
+
+fn evaluate(session: *const C4Session, budget: C4Budget) -> (i32, C4Evaluation) {
+    let mut out = C4Evaluation::default();
+    let code = unsafe { c4_session_evaluate(session, budget, &mut out) };
+    (code, out)
+}
+
+fn column_scores(session: *const C4Session, budget: C4Budget) -> (i32, C4ColumnScores) {
+    let mut out = C4ColumnScores::default();
+    let code = unsafe { c4_session_column_scores(session, budget, &mut out) };
+    (code, out)
+}
+
+#[test]
+fn evaluate_matches_the_naive_solver() {
+    let session = c4_session_new();
+    assert_eq!(set_position(session, POSITION), C4_OK);
+
+    let (code, out) = evaluate(session, UNLIMITED);
+    let expected = naive::evaluate_position(parse_position::<StateBitboard>(POSITION).unwrap()).eval;
+
+    assert_eq!(code, C4_OK);
+    assert!(out.exact);
+    assert_eq!(out.score, expected);
+    assert!(out.nodes > 0);
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn column_scores_pick_a_best_move() {
+    let session = c4_session_new();
+    assert_eq!(set_position(session, POSITION), C4_OK);
+
+    let (code, out) = column_scores(session, UNLIMITED);
+    let (_, eval) = evaluate(session, UNLIMITED);
+
+    assert_eq!(code, C4_OK);
+    assert!(out.exact);
+
+    let best_move = usize::try_from(out.best_move).unwrap();
+    assert_eq!(out.scores[best_move], eval.score);
+    assert!(out.scores.iter().all(|&score| score == C4_FULL_COLUMN || score <= eval.score));
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn full_columns_are_marked() {
+    let session = c4_session_new();
+    assert_eq!(set_position(session, "444444"), C4_OK);
+
+    let (code, out) = column_scores(session, C4Budget { time_ms: 0, max_nodes: 1_000 });
+
+    assert_eq!(code, C4_OK);
+    assert_eq!(out.scores[3], C4_FULL_COLUMN);
+    assert_ne!(out.best_move, 3);
+    assert!(out.scores.iter().enumerate().all(|(col, &score)| col == 3 || score != C4_FULL_COLUMN));
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn a_small_budget_gives_an_inexact_result() {
+    let session = c4_session_new();
+    let (code, out) = evaluate(session, C4Budget { time_ms: 0, max_nodes: 100 });
+
+    assert_eq!(code, C4_OK);
+    assert!(!out.exact);
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn grid_positions_are_accepted() {
+    let session = c4_session_new();
+    let grid = format_grid(&parse_position::<StateBitboard>(POSITION).unwrap());
+    assert_eq!(set_position(session, &grid), C4_OK);
+
+    let (code, from_grid) = evaluate(session, UNLIMITED);
+    assert_eq!(code, C4_OK);
+
+    assert_eq!(set_position(session, POSITION), C4_OK);
+    assert_eq!(evaluate(session, UNLIMITED).1.score, from_grid.score);
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn invalid_positions_are_rejected_and_keep_the_old_one() {
+    let session = c4_session_new();
+    assert_eq!(set_position(session, POSITION), C4_OK);
+    let (_, before) = evaluate(session, UNLIMITED);
+
+    assert_eq!(set_position(session, "48"), C4_ERROR_INVALID_POSITION);
+    assert_eq!(set_position(session, "4444444"), C4_ERROR_INVALID_POSITION);
+    assert_eq!(evaluate(session, UNLIMITED).1.score, before.score);
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn finished_games_cannot_be_evaluated() {
+    let session = c4_session_new();
+    assert_eq!(set_position(session, "1212121"), C4_OK);
+
+    assert_eq!(evaluate(session, UNLIMITED).0, C4_ERROR_GAME_OVER);
+    assert_eq!(column_scores(session, UNLIMITED).0, C4_ERROR_GAME_OVER);
+
+    unsafe { c4_session_free(session) };
+}
+
+#[test]
+fn null_pointers_are_reported() {
+    let session = c4_session_new();
+
+    assert_eq!(unsafe { c4_session_set_position(ptr::null_mut(), c"4".as_ptr()) }, C4_ERROR_NULL_POINTER);
+    assert_eq!(unsafe { c4_session_set_position(session, ptr::null()) }, C4_ERROR_NULL_POINTER);
+    assert_eq!(evaluate(ptr::null(), UNLIMITED).0, C4_ERROR_NULL_POINTER);
+    assert_eq!(unsafe { c4_session_evaluate(session, UNLIMITED, ptr::null_mut()) }, C4_ERROR_NULL_POINTER);
+    assert_eq!(unsafe { c4_session_column_scores(session, UNLIMITED, ptr::null_mut()) }, C4_ERROR_NULL_POINTER);
+
+    unsafe {
+        c4_session_free(session);
+        c4_session_free(ptr::null_mut());
+    }
+}
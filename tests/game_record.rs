@@ -0,0 +1,17 @@
+use software_testing_project::connect_four::game_record::GameRecord;
+
+#[test]
+fn comments_round_trip() {
+    let comments = ["a {brace} inside", "ends with a backslash \\", "[%eval +3] not an eval", "\\} and \\\\"];
+    let mut record = GameRecord::from_moves("first", "second", &[3, 3, 4, 4]);
+
+    for (recorded, comment) in record.moves.iter_mut().zip(comments) {
+        recorded.comment = Some(comment.to_string());
+    }
+
+    record.moves[0].eval = Some(2);
+    record.moves[2].eval = Some(-1);
+
+    let parsed: GameRecord = record.to_string().parse().unwrap();
+    assert_eq!(parsed, record);
+}
@@ -0,0 +1,61 @@
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::mcts::{agreement_with_solver, Mcts, MctsBudget};
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSolver, SolverConfig};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const SEED: u64 = 1;
+const ITERATIONS: MctsBudget = MctsBudget::Iterations(2000);
+
+// the first player wins by playing column 1 again
+const IMMEDIATE_WIN: &str = "121212";
+// the second player has to block the first player's three in column 1
+const THREAT: &str = "12131";
+// late enough for the exact solver to score every column
+const LATE_POSITIONS: [&str; 2] = ["43523412255117766131", "435234122551177661366"];
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+#[test]
+fn an_immediate_win_is_found() {
+    let result = Mcts::new(SEED).search(&state(IMMEDIATE_WIN), ITERATIONS);
+
+    assert_eq!(result.best_move(), Some(0));
+    assert_eq!(result.iterations, 2000);
+    assert!(result.win_rate(0).unwrap() > 0.9);
+}
+
+#[test]
+fn a_threat_is_blocked() {
+    assert_eq!(Mcts::new(SEED).search(&state(THREAT), ITERATIONS).best_move(), Some(0));
+}
+
+#[test]
+fn the_same_seed_searches_alike() {
+    let first = Mcts::new(SEED).search(&state(THREAT), ITERATIONS);
+    let second = Mcts::new(SEED).search(&state(THREAT), ITERATIONS);
+
+    assert_eq!(first.visits, second.visits);
+    assert_eq!(first.rewards, second.rewards);
+}
+
+#[test]
+fn late_moves_agree_with_the_solver() {
+    let states: Vec<StateBitboard> = LATE_POSITIONS.iter().map(|moves| state(moves)).collect();
+    let agreement = agreement_with_solver(&states, ITERATIONS, SEED, &CachingSolver::new(SolverConfig::default()));
+
+    assert_eq!(agreement.positions, LATE_POSITIONS.len());
+    assert_eq!(agreement.optimal_moves, LATE_POSITIONS.len());
+    assert_eq!((agreement.optimal_rate(), agreement.average_score_loss()), (1.0, 0.0));
+}
+
+#[test]
+fn positions_the_solver_cannot_score_are_left_out() {
+    let states: Vec<StateBitboard> = LATE_POSITIONS.iter().map(|moves| state(moves)).collect();
+    let budget = SearchBudget::unlimited().with_max_nodes(10);
+    let solver = CachingSolver::new(SolverConfig::default().with_budget(budget));
+
+    assert_eq!(agreement_with_solver(&states, ITERATIONS, SEED, &solver).positions, 0);
+}
@@ -0,0 +1,32 @@
+use software_testing_project::connect_four::perft::{perft, perft_divide, verify_published, PUBLISHED_PERFT};
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// deeper counts take too long in a debug build
+const MAX_DEPTH: usize = 8;
+
+fn check_published<S: State>() {
+    if let Err(mismatch) = verify_published::<S>(MAX_DEPTH) {
+        panic!("depth {}: expected {}, counted {}", mismatch.depth, mismatch.expected, mismatch.actual);
+    }
+}
+
+#[test]
+fn the_array_state_matches_the_published_counts() {
+    check_published::<StateArray>();
+}
+
+#[test]
+fn the_bitboard_state_matches_the_published_counts() {
+    check_published::<StateBitboard>();
+}
+
+#[test]
+fn perft_divide_sums_to_perft() {
+    let state = StateBitboard::start_state();
+    let divided: usize = perft_divide(&state, 5).iter().flatten().sum();
+
+    assert_eq!(divided, perft(&state, 5));
+    assert_eq!(divided, PUBLISHED_PERFT[5]);
+}
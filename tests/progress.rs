@@ -0,0 +1,33 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::progress::SearchProgress;
+use software_testing_project::connect_four::solver::{SolverConfig, SolverRegistry};
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// long enough for many progress reports
+const POSITION: &str = "43523412255117766";
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+#[test]
+fn progress_reports_count_up_to_the_nodes_searched() {
+    for name in ["caching", "threads"] {
+        let reports = Arc::new(Mutex::new(Vec::<SearchProgress>::new()));
+        let observer_reports = reports.clone();
+        let config = SolverConfig::default().with_progress(
+            Arc::new(move |progress: &SearchProgress| observer_reports.lock().unwrap().push(progress.clone())),
+            Duration::ZERO,
+        );
+
+        let ret = SolverRegistry::with_default_solvers().create(name, config).unwrap().evaluate(state(POSITION));
+        let reports = reports.lock().unwrap();
+
+        assert!(reports.len() > 1, "{name}");
+        assert!(reports.windows(2).all(|pair| pair[0].nodes < pair[1].nodes && pair[0].elapsed <= pair[1].elapsed), "{name}");
+        assert!(reports.iter().all(|report| report.root_move.is_some()), "{name}");
+        assert!(reports.last().unwrap().nodes <= ret.states_evaluated, "{name}");
+    }
+}
@@ -0,0 +1,51 @@
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::proof_number::{prove, solve, ProofTarget, DEFAULT_MAX_NODES};
+use software_testing_project::connect_four::solver::{NaiveSolver, Solver, SolverConfig};
+use software_testing_project::connect_four::solver_util::Outcome;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// a win, a draw and two losses for the player to move, late enough for the naive solver
+const LATE_POSITIONS: [(&str, Outcome); 4] = [
+    ("43523412255117766131", Outcome::Win),
+    ("4352341225511776613433442", Outcome::Draw),
+    ("435234122551177661366", Outcome::Loss),
+    ("43523412255117766", Outcome::Loss),
+];
+
+const EARLY_POSITION: &str = "4444555";
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+#[test]
+fn outcomes_match_the_naive_solver() {
+    let naive = NaiveSolver::new(SolverConfig::default());
+
+    for (moves, outcome) in LATE_POSITIONS {
+        let result = solve(state(moves), DEFAULT_MAX_NODES);
+
+        assert_eq!(Outcome::from_eval(naive.evaluate(state(moves)).eval), outcome, "{moves}");
+        assert_eq!(result.outcome, Some(outcome), "{moves}");
+        assert!(result.nodes_expanded > 0, "{moves}");
+    }
+}
+
+#[test]
+fn each_target_is_proven_or_disproven() {
+    for (moves, outcome) in LATE_POSITIONS {
+        let (win, _) = prove(state(moves), ProofTarget::Win, DEFAULT_MAX_NODES);
+        let (at_least_draw, _) = prove(state(moves), ProofTarget::AtLeastDraw, DEFAULT_MAX_NODES);
+
+        assert_eq!(win, Some(outcome == Outcome::Win), "{moves}");
+        assert_eq!(at_least_draw, Some(outcome != Outcome::Loss), "{moves}");
+    }
+}
+
+#[test]
+fn an_exhausted_node_limit_leaves_the_outcome_unknown() {
+    let result = solve(state(EARLY_POSITION), 1000);
+
+    assert_eq!(result.outcome, None);
+    assert!(result.nodes_expanded < 1000);
+}
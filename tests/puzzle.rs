@@ -0,0 +1,51 @@
+use software_testing_project::connect_four::puzzle::{random_positions, read_puzzle_file, search_puzzles, write_puzzle_file, Puzzle};
+use software_testing_project::connect_four::solver::{CachingSession, CachingSolver, Solver, SolverConfig};
+use software_testing_project::connect_four::solver_util::COLS;
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+const SEED: u64 = 1;
+const MOVES_MADE: usize = 24;
+const MIN_WIN_LENGTH: usize = 3;
+
+fn puzzles() -> Vec<Puzzle<StateBitboard>> {
+    let solver = CachingSession::new(SolverConfig::default());
+    let puzzles = search_puzzles(random_positions(SEED, 200, MOVES_MADE), &solver, MIN_WIN_LENGTH, 2);
+
+    assert_eq!(puzzles.len(), 2);
+    puzzles
+}
+
+#[test]
+fn puzzles_have_exactly_one_winning_column() {
+    // a solver without the session's cache checks every column again
+    let solver = CachingSolver::new(SolverConfig::default());
+
+    for puzzle in puzzles() {
+        let winning_cols: Vec<usize> = (0..COLS)
+            .filter(|&col| puzzle.state.play_move(col).is_some_and(|next_state| {
+                next_state.is_win() || -solver.evaluate(next_state).eval > 0
+            }))
+            .collect();
+
+        assert_eq!(winning_cols, [puzzle.solution]);
+        assert!(puzzle.win_length >= MIN_WIN_LENGTH);
+    }
+}
+
+#[test]
+fn puzzle_files_round_trip() {
+    let path = std::env::temp_dir().join(format!("puzzle-test-{}", std::process::id()));
+    let puzzles = puzzles();
+
+    write_puzzle_file(&path, &puzzles).unwrap();
+    let read = read_puzzle_file::<StateBitboard, _>(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(read.len(), puzzles.len());
+
+    for (read, puzzle) in read.iter().zip(&puzzles) {
+        assert!(read.state == puzzle.state);
+        assert_eq!((read.solution, read.win_length, read.column_scores), (puzzle.solution, puzzle.win_length, puzzle.column_scores));
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use software_testing_project::connect_four::server::Server;
+use software_testing_project::connect_four::service::Response;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// both solve quickly, even in a debug build
+const LATE_POSITIONS: [(&str, i32); 2] = [("435234122551177661366", -1), ("4352341225511776613", 0)];
+
+// far too early in the game to be solved before it is cancelled
+const EARLY_POSITION: &str = "4444555";
+
+fn start_server(max_concurrent_solves: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Server::<StateBitboard>::new(max_concurrent_solves);
+
+    thread::spawn(move || server.serve_tcp(listener));
+    addr
+}
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(addr: SocketAddr) -> Self {
+        let writer = TcpStream::connect(addr).unwrap();
+        let reader = BufReader::new(writer.try_clone().unwrap());
+        Self { reader, writer }
+    }
+
+    fn send(&mut self, request: Value) {
+        writeln!(self.writer, "{request}").unwrap();
+        self.writer.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Response {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+}
+
+#[test]
+fn concurrent_clients_get_their_own_solves() {
+    let addr = start_server(4);
+
+    let clients: Vec<_> = ["caching", "threads"]
+        .into_iter()
+        .map(|solver| thread::spawn(move || {
+            let mut client = Client::connect(addr);
+
+            for (id, (position, _)) in LATE_POSITIONS.iter().enumerate() {
+                client.send(json!({ "id": id, "position": position, "solver": solver }));
+            }
+
+            (0..LATE_POSITIONS.len()).map(|_| client.receive()).collect::<Vec<_>>()
+        }))
+        .collect();
+
+    for client in clients {
+        for response in client.join().unwrap() {
+            let (_, score) = LATE_POSITIONS[response.id.as_u64().unwrap() as usize];
+
+            assert!(response.ok, "{:?}", response.error);
+            assert_eq!((response.eval, response.exact), (Some(score), Some(true)));
+        }
+    }
+}
+
+#[test]
+fn requests_over_the_solve_limit_are_rejected() {
+    let addr = start_server(1);
+    let mut solving = Client::connect(addr);
+    let mut rejected = Client::connect(addr);
+
+    solving.send(json!({ "id": 1, "position": EARLY_POSITION }));
+
+    // the first request holds the only solve until it is cancelled, whichever client asks
+    let mut response = Response::default();
+
+    for _ in 0..100 {
+        rejected.send(json!({ "id": 2, "position": LATE_POSITIONS[0].0 }));
+        response = rejected.receive();
+
+        if !response.ok {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(response.id, json!(2));
+    assert!(!response.ok && response.error.unwrap().contains("already solving"));
+
+    solving.send(json!({ "cancel": 1 }));
+    solving.receive();
+
+    rejected.send(json!({ "id": 3, "position": LATE_POSITIONS[0].0 }));
+    assert_eq!(rejected.receive().eval, Some(LATE_POSITIONS[0].1));
+}
+
+#[test]
+fn cancelling_stops_a_running_solve() {
+    let addr = start_server(2);
+    let mut client = Client::connect(addr);
+
+    client.send(json!({ "id": "slow", "position": EARLY_POSITION }));
+    thread::sleep(Duration::from_millis(100));
+    client.send(json!({ "cancel": "slow" }));
+
+    let response = client.receive();
+
+    assert_eq!(response.id, json!("slow"));
+    assert!(response.cancelled);
+    assert_ne!(response.exact, Some(true));
+}
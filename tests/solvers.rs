@@ -0,0 +1,95 @@
+use software_testing_project::connect_four::{cache_strategy, naive, threads};
+use software_testing_project::connect_four::budget::SearchBudget;
+use software_testing_project::connect_four::equivalence::{check_random_games, check_solvers};
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSession, CachingSolver, NaiveSolver, Solver, SolverConfig, ThreadsSession};
+use software_testing_project::connect_four::solver_util::COLS;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+
+// late enough in the game that every test solves it quickly, even in a debug build
+const LATE_POSITION: &str = "435234122551177661366";
+
+const EQUIVALENCE_SEED: u64 = 7;
+// the positions from this ply on are quick to solve with the naive solver, even in a debug build
+const EQUIVALENCE_MIN_MOVES_MADE: usize = 22;
+
+// the cached bounds of this position's children close the window on the way down. searching on
+// with the closed window used to cache its edge as a proven bound and give -2
+const CLOSED_WINDOW_POSITION: &str = "O....../O....../XOXO.../XOXX.O./XOOXOX./OXXXOOX";
+
+fn position(text: &str) -> StateBitboard {
+    parse_position(text).unwrap()
+}
+
+#[test]
+fn the_naive_solver_scores_the_closed_window_position() {
+    assert_eq!(naive::evaluate_position(position(CLOSED_WINDOW_POSITION)).eval, -1);
+}
+
+#[test]
+fn the_caching_solver_stops_at_a_window_closed_by_cached_bounds() {
+    assert_eq!(cache_strategy::evaluate_position(position(CLOSED_WINDOW_POSITION)).eval, -1);
+}
+
+#[test]
+fn the_threads_solver_stops_at_a_window_closed_by_cached_bounds() {
+    assert_eq!(threads::evaluate_position(position(CLOSED_WINDOW_POSITION)).eval, -1);
+}
+
+// a session keeps its cache between searches, so a bound cached from a closed window would show up
+// in the searches after the first
+fn check_repeated_searches(solver: &dyn Solver<StateBitboard>) {
+    let state = position(CLOSED_WINDOW_POSITION);
+    let expected = NaiveSolver::new(SolverConfig::default()).column_scores(&state);
+
+    assert_eq!(solver.evaluate(state.clone()).eval, -1);
+    assert_eq!(solver.column_scores(&state).exact_scores(), expected.exact_scores());
+    assert_eq!(solver.evaluate(state).eval, -1);
+}
+
+#[test]
+fn a_caching_session_keeps_no_bound_from_a_closed_window() {
+    check_repeated_searches(&CachingSession::new(SolverConfig::default()));
+}
+
+#[test]
+fn a_threads_session_keeps_no_bound_from_a_closed_window() {
+    check_repeated_searches(&ThreadsSession::new(SolverConfig::default()));
+}
+
+#[test]
+fn column_scores_share_the_solver_budget() {
+    let budget = SearchBudget::unlimited().with_max_nodes(1_000);
+    let solver = CachingSolver::new(SolverConfig::default().with_budget(budget));
+    let scores = solver.column_scores(&position("4444555"));
+
+    assert!(!scores.is_exact());
+    assert!(scores.exact_scores().is_none());
+    assert!(scores.states_evaluated <= 1_000 + COLS, "{} states evaluated", scores.states_evaluated);
+}
+
+#[test]
+fn column_scores_are_exact_without_a_budget() {
+    let state = position(LATE_POSITION);
+    let scores = CachingSolver::new(SolverConfig::default()).column_scores(&state);
+    let best_score = scores.scores.iter().flatten().max().copied();
+
+    assert!(scores.is_exact());
+    assert_eq!(best_score, Some(naive::evaluate_position(state).eval));
+    assert_eq!(scores.best_column().and_then(|col| scores.scores[col]), best_score);
+}
+
+#[test]
+fn both_representations_play_random_games_alike() {
+    if let Err(divergence) = check_random_games(EQUIVALENCE_SEED, 200) {
+        panic!("{divergence}");
+    }
+}
+
+#[test]
+fn every_solver_agrees_on_both_representations() {
+    match check_solvers(EQUIVALENCE_SEED, 20, EQUIVALENCE_MIN_MOVES_MADE) {
+        Ok(positions) => assert!(positions > 0),
+        Err(divergence) => panic!("{divergence}"),
+    }
+}
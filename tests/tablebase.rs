@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::PathBuf;
+use software_testing_project::connect_four::naive;
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver_util::BOARD_SIZE;
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_array::StateArray;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+use software_testing_project::connect_four::tablebase::Tablebase;
+
+// twelve empty cells, so that generating a tablebase below it is quick even in a debug build
+const ROOT: &str = "OX..OO./OO..XX./XO.OOO./XX.XXX./XO.OXXX/OX.OOOX";
+const MAX_EMPTY_CELLS: usize = 10;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tablebase-test-{}-{name}", std::process::id()))
+}
+
+fn positions_below(state: StateBitboard, positions: &mut Vec<StateBitboard>) {
+    if state.is_win() || state.board_full() {
+        return
+    }
+
+    positions.push(state.clone());
+
+    for next_state in state.next_states() {
+        positions_below(next_state, positions);
+    }
+}
+
+#[test]
+fn generated_scores_match_the_naive_solver() {
+    let root: StateBitboard = parse_position(ROOT).unwrap();
+    let tablebase = Tablebase::generate(std::slice::from_ref(&root), MAX_EMPTY_CELLS);
+
+    let mut positions = vec![];
+    positions_below(root.clone(), &mut positions);
+    assert!(!tablebase.is_empty());
+    assert_eq!(tablebase.probe(&root), None);
+
+    let endgame = positions.into_iter().filter(|state| BOARD_SIZE - state.moves_made() <= MAX_EMPTY_CELLS);
+
+    for state in endgame.step_by(97) {
+        assert_eq!(tablebase.probe(&state), Some(naive::evaluate_position(state.clone()).eval), "{}", state.decode());
+    }
+}
+
+#[test]
+fn a_saved_tablebase_loads_for_its_representation_only() {
+    let root: StateBitboard = parse_position(ROOT).unwrap();
+    let tablebase = Tablebase::generate(std::slice::from_ref(&root), MAX_EMPTY_CELLS);
+    let path = temp_path("saved");
+    tablebase.save(&path).unwrap();
+
+    let loaded = Tablebase::load::<StateBitboard, _>(&path).unwrap();
+    assert_eq!(loaded.len(), tablebase.len());
+    assert_eq!(loaded.max_empty_cells(), MAX_EMPTY_CELLS);
+
+    assert!(Tablebase::load::<StateArray, _>(&path).is_err());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unsorted_entries_are_rejected() {
+    let root: StateBitboard = parse_position(ROOT).unwrap();
+    let path = temp_path("unsorted");
+    Tablebase::generate(std::slice::from_ref(&root), MAX_EMPTY_CELLS).save(&path).unwrap();
+
+    // swaps the first two entries, which follow the header, the size and the entry count
+    let mut bytes = fs::read(&path).unwrap();
+    let entries = bytes.len() - Tablebase::load::<StateBitboard, _>(&path).unwrap().len() * 9;
+    let (first, second) = bytes[entries..].split_at_mut(9);
+    first.swap_with_slice(&mut second[..9]);
+    fs::write(&path, bytes).unwrap();
+
+    let err = Tablebase::load::<StateBitboard, _>(&path).err().unwrap();
+    assert!(err.to_string().contains("not sorted"), "{err}");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn truncated_files_are_rejected() {
+    let root: StateBitboard = parse_position(ROOT).unwrap();
+    let path = temp_path("truncated");
+    Tablebase::generate(std::slice::from_ref(&root), MAX_EMPTY_CELLS).save(&path).unwrap();
+
+    let bytes = fs::read(&path).unwrap();
+    fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+
+    let err = Tablebase::load::<StateBitboard, _>(&path).err().unwrap();
+    assert!(err.to_string().contains("truncated"), "{err}");
+    fs::remove_file(path).unwrap();
+}
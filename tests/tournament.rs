@@ -0,0 +1,84 @@
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::state::State;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+use software_testing_project::connect_four::tournament::{create_player, Record, Tournament};
+
+const SEED: u64 = 3;
+
+// one opening with X to move and one with O to move
+const OPENINGS: [&str; 2] = ["", "4"];
+
+// drawn positions late enough for the exact player to solve every move, even in a debug build
+const DRAWN_OPENINGS: [&str; 2] = ["4352341225511776613433442", "43523412255117766134334425"];
+
+fn openings(moves: &[&str]) -> Vec<StateBitboard> {
+    moves.iter().map(|moves| parse_position(moves).unwrap()).collect()
+}
+
+fn tournament(openings: Vec<StateBitboard>, engines: &[&'static str]) -> Tournament<StateBitboard> {
+    engines.iter().fold(Tournament::new(openings, SEED), |tournament, &spec| {
+        tournament.with_entrant(spec, move |seed| create_player(spec, seed).unwrap())
+    })
+}
+
+fn assert_interval(record: Record, expected: (f64, f64)) {
+    let (low, high) = record.confidence_interval();
+    assert!((low - expected.0).abs() < 1e-4 && (high - expected.1).abs() < 1e-4, "{low} {high}");
+}
+
+#[test]
+fn confidence_intervals_are_wilson_intervals() {
+    assert_interval(Record { wins: 8, draws: 0, losses: 2 }, (0.4902, 0.9433));
+    assert_interval(Record { wins: 10, draws: 0, losses: 0 }, (0.7225, 1.0));
+    assert_interval(Record { wins: 0, draws: 0, losses: 0 }, (0.0, 1.0));
+
+    let record = Record { wins: 1, draws: 2, losses: 1 };
+    assert_eq!(record.score(), 0.5);
+
+    let (low, high) = record.confidence_interval();
+    assert!((low + high - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn each_opening_is_played_from_both_sides() {
+    let tournament = tournament(openings(&OPENINGS), &["random", "depth:2"]);
+    let mut games = vec![];
+    let report = tournament.run(|game| games.push(game.clone()));
+
+    assert_eq!(games.len(), tournament.game_count());
+    assert_eq!(report.games, OPENINGS.len() * 2);
+
+    for opening in 0..OPENINGS.len() {
+        let x_players: Vec<usize> = games
+            .iter()
+            .filter(|game| game.opening == opening)
+            .map(|game| if game.first_plays_x { game.first } else { game.second })
+            .collect();
+
+        assert_eq!(x_players.len(), 2);
+        assert_ne!(x_players[0], x_players[1]);
+        assert_eq!(games.iter().find(|game| game.opening == opening).unwrap().first_plays_x, opening == 0);
+    }
+
+    // every game is legal from its opening
+    for game in &games {
+        let mut state = parse_position::<StateBitboard>(OPENINGS[game.opening]).unwrap();
+
+        for &col in &game.moves {
+            state = state.play_move(col).unwrap();
+        }
+
+        assert!(!game.forfeit);
+        assert_eq!(game.winner.is_some(), state.is_win());
+    }
+}
+
+#[test]
+fn the_exact_player_beats_a_random_one() {
+    let report = tournament(openings(&DRAWN_OPENINGS), &["exact", "random"]).run(|_| ());
+    let exact = report.engines[0].record;
+
+    assert_eq!(exact.losses, 0);
+    assert!(exact.score() > 0.5);
+    assert_eq!(report.matchups[0].record, exact);
+}
@@ -0,0 +1,42 @@
+use serde_json::Value;
+use software_testing_project::connect_four::move_string::parse_position;
+use software_testing_project::connect_four::solver::{CachingSolver, NaiveSolver, Solver, SolverConfig};
+use software_testing_project::connect_four::solver_util::BOARD_SIZE;
+use software_testing_project::connect_four::state_bitboard::StateBitboard;
+use software_testing_project::connect_four::trace::TraceConfig;
+
+const LATE_POSITION: &str = "435234122551177661366";
+
+fn state(moves: &str) -> StateBitboard {
+    parse_position(moves).unwrap()
+}
+
+#[test]
+fn a_full_trace_has_a_node_for_every_state_evaluated() {
+    let trace_config = TraceConfig { max_depth: BOARD_SIZE, max_nodes: usize::MAX };
+    let solvers: [Box<dyn Solver<StateBitboard>>; 2] = [
+        Box::new(NaiveSolver::new(SolverConfig::default().with_trace(trace_config))),
+        Box::new(CachingSolver::new(SolverConfig::default().with_trace(trace_config))),
+    ];
+
+    for solver in solvers {
+        let ret = solver.evaluate(state(LATE_POSITION));
+        let tree = ret.trace.as_ref().expect("the search records a tree");
+        let json: Value = serde_json::from_str(&tree.to_json()).unwrap();
+        let nodes = json["nodes"].as_array().unwrap();
+
+        assert_eq!(nodes.len(), ret.states_evaluated, "{}", solver.name());
+        assert_eq!(json["truncated"], false);
+        assert_eq!(nodes[0]["value"], ret.eval);
+
+        // every node but the root is the child of the node it names as its parent
+        for node in &nodes[1..] {
+            let parent = &nodes[node["parent"].as_u64().unwrap() as usize];
+            assert!(parent["children"].as_array().unwrap().contains(&node["id"]));
+        }
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph search {"));
+        assert_eq!(dot.matches(" -> ").count(), nodes.len() - 1);
+    }
+}